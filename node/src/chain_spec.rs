@@ -2,11 +2,11 @@ use std::vec;
 
 use node_template_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY, TemplateKittiesConfig, AssetsConfig,
+	SystemConfig, WASM_BINARY, TemplateKittiesConfig, AssetsConfig, TreasuryAccount, EXISTENTIAL_DEPOSIT,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::{sr25519, Pair, Public};
+use sp_core::{sr25519, Get, Pair, Public};
 use sp_finality_grandpa::AuthorityId as GrandpaId;
 use sp_runtime::traits::{IdentifyAccount, Verify};
 use serde_json::json;
@@ -150,7 +150,14 @@ fn testnet_genesis(
 		},
 		balances: BalancesConfig {
 			// Configure endowed accounts with initial balance of 1 << 60.
-			balances: endowed_accounts.iter().cloned().map(|k| (k, 1 << 60)).collect(),
+			// The treasury takes the marketplace fees, so it must exist to receive fees below the
+			// existential deposit.
+			balances: endowed_accounts
+				.iter()
+				.cloned()
+				.map(|k| (k, 1 << 60))
+				.chain(std::iter::once((TreasuryAccount::get(), EXISTENTIAL_DEPOSIT)))
+				.collect(),
 		},
 		aura: AuraConfig {
 			authorities: initial_authorities.iter().map(|x| (x.0.clone())).collect(),
@@ -176,7 +183,8 @@ fn testnet_genesis(
 			assets: vec![],
 			metadata: vec![],
 			accounts: vec![],
-		} 
+		},
+		treasury: Default::default(),
 
 	}
 }
@@ -0,0 +1,275 @@
+//! RPC interface for the kitties pallet.
+//!
+//! Kitties are decoded on the node so clients get plain JSON: the DNA as hex,
+//! the name, gender, traits and life stage as strings and the owner as an SS58 address.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Codec;
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{crypto::Ss58Codec, Bytes};
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_kitties_runtime_api::KittiesApi as KittiesRuntimeApi;
+use pallet_kitties_runtime_api::{KittyIndex, KittyInfo, KittyTraits};
+
+/// A kitty as served over RPC.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcKitty {
+	pub id: KittyIndex,
+	pub dna: Bytes,
+	pub name: String,
+	pub gender: String,
+	pub traits: RpcKittyTraits,
+	pub parents: Option<(KittyIndex, KittyIndex)>,
+	pub generation: u16,
+	pub life_stage: String,
+	pub price: Option<NumberOrHex>,
+	pub price_asset: Option<NumberOrHex>,
+	pub owner: String,
+	pub created_date: u64,
+}
+
+/// Traits of a kitty as served over RPC.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcKittyTraits {
+	pub fur_color: String,
+	pub eye_color: String,
+	pub pattern: String,
+	pub rarity: String,
+	pub rarity_score: u32,
+}
+
+impl From<KittyTraits> for RpcKittyTraits {
+	fn from(traits: KittyTraits) -> Self {
+		RpcKittyTraits {
+			fur_color: format!("{:?}", traits.fur_color),
+			eye_color: format!("{:?}", traits.eye_color),
+			pattern: format!("{:?}", traits.pattern),
+			rarity: format!("{:?}", traits.rarity),
+			rarity_score: traits.rarity_score,
+		}
+	}
+}
+
+impl<AccountId, Balance, Moment, AssetId> From<KittyInfo<AccountId, Balance, Moment, AssetId>> for RpcKitty
+where
+	AccountId: Ss58Codec,
+	Balance: Into<NumberOrHex>,
+	Moment: Into<u64>,
+	AssetId: Into<NumberOrHex>,
+{
+	fn from(info: KittyInfo<AccountId, Balance, Moment, AssetId>) -> Self {
+		RpcKitty {
+			id: info.id,
+			dna: info.dna.to_vec().into(),
+			name: String::from_utf8_lossy(&info.name).into_owned(),
+			gender: format!("{:?}", info.gender),
+			traits: info.traits.into(),
+			parents: info.parents,
+			generation: info.generation,
+			life_stage: format!("{:?}", info.life_stage),
+			price: info.price.map(Into::into),
+			price_asset: info.price_asset.map(Into::into),
+			owner: info.owner.to_ss58check(),
+			created_date: info.created_date.into(),
+		}
+	}
+}
+
+#[rpc(client, server)]
+pub trait KittiesApi<BlockHash, Hash, AccountId> {
+	#[method(name = "kitties_kitty")]
+	fn kitty(&self, kitty_id: KittyIndex, at: Option<BlockHash>) -> RpcResult<Option<RpcKitty>>;
+
+	#[method(name = "kitties_kittyByHash")]
+	fn kitty_by_hash(&self, kitty_hash: Hash, at: Option<BlockHash>) -> RpcResult<Option<RpcKitty>>;
+
+	#[method(name = "kitties_kittyTraits")]
+	fn kitty_traits(&self, kitty_id: KittyIndex, at: Option<BlockHash>) -> RpcResult<Option<RpcKittyTraits>>;
+
+	#[method(name = "kitties_ancestry")]
+	fn ancestry(
+		&self,
+		kitty_id: KittyIndex,
+		depth: u32,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<(KittyIndex, u32)>>;
+
+	#[method(name = "kitties_children")]
+	fn children(&self, kitty_id: KittyIndex, at: Option<BlockHash>) -> RpcResult<Vec<KittyIndex>>;
+
+	#[method(name = "kitties_kittiesOf")]
+	fn kitties_of(&self, owner: AccountId, at: Option<BlockHash>) -> RpcResult<Vec<RpcKitty>>;
+
+	#[method(name = "kitties_listedKitties")]
+	fn listed_kitties(
+		&self,
+		offset: u32,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<RpcKitty>>;
+
+	#[method(name = "kitties_count")]
+	fn count(&self, at: Option<BlockHash>) -> RpcResult<u32>;
+
+	#[method(name = "kitties_userOf")]
+	fn user_of(&self, kitty_id: KittyIndex, at: Option<BlockHash>) -> RpcResult<Option<String>>;
+}
+
+/// Provides RPC methods to query kitties.
+pub struct Kitties<C, Block, Balance, Moment, AssetId> {
+	client: Arc<C>,
+	_marker: PhantomData<(Block, Balance, Moment, AssetId)>,
+}
+
+impl<C, Block, Balance, Moment, AssetId> Kitties<C, Block, Balance, Moment, AssetId> {
+	/// Create new `Kitties` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error code for a failed runtime call.
+const RUNTIME_ERROR: i32 = 1;
+
+fn runtime_error(e: impl std::fmt::Debug) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(
+		RUNTIME_ERROR,
+		"Unable to query kitties.",
+		Some(format!("{:?}", e)),
+	))
+	.into()
+}
+
+impl<C, Block, Hash, AccountId, Balance, Moment, AssetId>
+	KittiesApiServer<<Block as BlockT>::Hash, Hash, AccountId> for Kitties<C, Block, Balance, Moment, AssetId>
+where
+	Block: BlockT,
+	C: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+	C::Api: KittiesRuntimeApi<Block, Hash, AccountId, Balance, Moment, AssetId>,
+	Hash: Codec + DeserializeOwned + Send + Sync + 'static,
+	AccountId: Codec + Ss58Codec + DeserializeOwned + Send + Sync + 'static,
+	Balance: Codec + Into<NumberOrHex> + Send + Sync + 'static,
+	Moment: Codec + Into<u64> + Send + Sync + 'static,
+	AssetId: Codec + Into<NumberOrHex> + Send + Sync + 'static,
+{
+	fn kitty(
+		&self,
+		kitty_id: KittyIndex,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<RpcKitty>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let kitty = api.kitty(&at, kitty_id).map_err(runtime_error)?;
+
+		Ok(kitty.map(Into::into))
+	}
+
+	fn kitty_by_hash(
+		&self,
+		kitty_hash: Hash,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<RpcKitty>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let kitty = api.kitty_by_hash(&at, kitty_hash).map_err(runtime_error)?;
+
+		Ok(kitty.map(Into::into))
+	}
+
+	fn kitty_traits(
+		&self,
+		kitty_id: KittyIndex,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<RpcKittyTraits>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let traits = api.kitty_traits(&at, kitty_id).map_err(runtime_error)?;
+
+		Ok(traits.map(Into::into))
+	}
+
+	fn ancestry(
+		&self,
+		kitty_id: KittyIndex,
+		depth: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<(KittyIndex, u32)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.ancestry(&at, kitty_id, depth).map_err(runtime_error)
+	}
+
+	fn children(
+		&self,
+		kitty_id: KittyIndex,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<KittyIndex>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.children(&at, kitty_id).map_err(runtime_error)
+	}
+
+	fn kitties_of(
+		&self,
+		owner: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<RpcKitty>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let kitties = api.kitties_of(&at, owner).map_err(runtime_error)?;
+
+		Ok(kitties.into_iter().map(Into::into).collect())
+	}
+
+	fn listed_kitties(
+		&self,
+		offset: u32,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<RpcKitty>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let kitties = api.listed_kitties(&at, offset, limit).map_err(runtime_error)?;
+
+		Ok(kitties.into_iter().map(Into::into).collect())
+	}
+
+	fn count(&self, at: Option<<Block as BlockT>::Hash>) -> RpcResult<u32> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.count(&at).map_err(runtime_error)
+	}
+
+	fn user_of(
+		&self,
+		kitty_id: KittyIndex,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<String>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let user = api.user_of(&at, kitty_id).map_err(runtime_error)?;
+
+		Ok(user.map(|user| user.to_ss58check()))
+	}
+}
@@ -0,0 +1,38 @@
+//! Runtime API definition for the kitties pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+pub use pallet_kitties::{Gender, KittyIndex, KittyInfo, KittyTraits};
+
+sp_api::decl_runtime_apis! {
+	pub trait KittiesApi<Hash, AccountId, Balance, Moment, AssetId> where
+		Hash: Codec,
+		AccountId: Codec,
+		Balance: Codec,
+		Moment: Codec,
+		AssetId: Codec,
+	{
+		fn kitty(kitty_id: KittyIndex) -> Option<KittyInfo<AccountId, Balance, Moment, AssetId>>;
+
+		fn kitty_by_hash(kitty_hash: Hash) -> Option<KittyInfo<AccountId, Balance, Moment, AssetId>>;
+
+		fn kitty_traits(kitty_id: KittyIndex) -> Option<KittyTraits>;
+
+		/// ancestors with their distance to the kitty, walking at most `depth` generations
+		fn ancestry(kitty_id: KittyIndex, depth: u32) -> Vec<(KittyIndex, u32)>;
+
+		fn children(kitty_id: KittyIndex) -> Vec<KittyIndex>;
+
+		fn kitties_of(owner: AccountId) -> Vec<KittyInfo<AccountId, Balance, Moment, AssetId>>;
+
+		fn listed_kitties(offset: u32, limit: u32) -> Vec<KittyInfo<AccountId, Balance, Moment, AssetId>>;
+
+		fn count() -> u32;
+
+		/// the account using a leased kitty
+		fn user_of(kitty_id: KittyIndex) -> Option<AccountId>;
+	}
+}
@@ -0,0 +1,96 @@
+//! Implementations for `nonfungibles` traits, all kitties live in a single `()` collection.
+
+use super::*;
+use frame_support::{
+	dispatch::DispatchResult,
+	ensure,
+	traits::tokens::nonfungibles::{Inspect, InspectEnumerable, Mutate, Transfer},
+};
+use sp_std::{boxed::Box, vec::Vec};
+
+impl<T: Config> Inspect<<T as frame_system::Config>::AccountId> for Pallet<T> {
+	type ItemId = KittyIndex;
+	type CollectionId = ();
+
+	fn owner(_collection: &Self::CollectionId, item: &Self::ItemId) -> Option<T::AccountId> {
+		Kitties::<T>::get(item).map(|kitty| kitty.owner())
+	}
+
+	/// the only attribute of a kitty is its `dna`
+	fn attribute(_collection: &Self::CollectionId, item: &Self::ItemId, key: &[u8]) -> Option<Vec<u8>> {
+		match key {
+			b"dna" => Kitties::<T>::get(item).map(|kitty| kitty.dna().to_vec()),
+			_ => None,
+		}
+	}
+
+	fn can_transfer(_collection: &Self::CollectionId, item: &Self::ItemId) -> bool {
+		Kitties::<T>::contains_key(item)
+			&& Pallet::<T>::ensure_not_locked(item).is_ok()
+			&& Pallet::<T>::ensure_cooled_down(item).is_ok()
+	}
+}
+
+impl<T: Config> InspectEnumerable<T::AccountId> for Pallet<T> {
+	fn collections() -> Box<dyn Iterator<Item = Self::CollectionId>> {
+		Box::new(sp_std::iter::once(()))
+	}
+
+	fn items(_collection: &Self::CollectionId) -> Box<dyn Iterator<Item = Self::ItemId>> {
+		Box::new(Kitties::<T>::iter_keys())
+	}
+
+	fn owned(who: &T::AccountId) -> Box<dyn Iterator<Item = (Self::CollectionId, Self::ItemId)>> {
+		Box::new(KittyOwner::<T>::get(who).into_iter().map(|kitty_id| ((), kitty_id)))
+	}
+
+	fn owned_in_collection(
+		_collection: &Self::CollectionId,
+		who: &T::AccountId,
+	) -> Box<dyn Iterator<Item = Self::ItemId>> {
+		Box::new(KittyOwner::<T>::get(who).into_iter())
+	}
+}
+
+impl<T: Config> Transfer<T::AccountId> for Pallet<T> {
+	fn transfer(_collection: &Self::CollectionId, item: &Self::ItemId, destination: &T::AccountId) -> DispatchResult {
+		let owner = Kitties::<T>::get(item).ok_or(Error::<T>::KittyNotExists)?.owner();
+
+		ensure!(owner != *destination, Error::<T>::TransferToSelf);
+
+		Pallet::<T>::ensure_not_locked(item)?;
+
+		Pallet::<T>::ensure_cooled_down(item)?;
+
+		Pallet::<T>::transfer_to(*item, destination)?;
+
+		Pallet::<T>::deposit_event(Event::KittyTranfered(*item, destination.clone()));
+
+		Ok(())
+	}
+}
+
+impl<T: Config> Mutate<T::AccountId> for Pallet<T> {
+	/// mint a kitty with random DNA under the given index, which must not be below `NextKittyIndex`
+	fn mint_into(_collection: &Self::CollectionId, item: &Self::ItemId, who: &T::AccountId) -> DispatchResult {
+		let kitty = Pallet::<T>::mint(who.clone());
+
+		Pallet::<T>::insert_kitty_with_id(*item, who, kitty)?;
+
+		Pallet::<T>::deposit_event(Event::KittyCreated(who.clone(), *item));
+
+		Ok(())
+	}
+
+	fn burn(
+		_collection: &Self::CollectionId,
+		item: &Self::ItemId,
+		maybe_check_owner: Option<&T::AccountId>,
+	) -> DispatchResult {
+		if let Some(check_owner) = maybe_check_owner {
+			ensure!(Pallet::<T>::is_kitty_owner(item, check_owner)?, Error::<T>::NotKittyOwner);
+		}
+
+		Pallet::<T>::do_burn(*item)
+	}
+}
@@ -6,6 +6,10 @@ pub mod types;
 
 pub mod weights;
 
+pub mod migrations;
+
+mod impl_nonfungibles;
+
 #[cfg(test)]
 mod mock;
 
@@ -19,11 +23,14 @@ mod benchmarking;
 pub mod pallet {
 	use frame_support::{pallet_prelude::{
 		*, ValueQuery, DispatchResult}, 
-		traits::{Randomness, Currency, Time}, 
-		ensure, transactional, BoundedVec};
-	use frame_support::sp_runtime::traits::Hash;
-	use frame_system::{pallet_prelude::OriginFor, ensure_signed};
+		traits::{Randomness, Currency, ReservableCurrency, BalanceStatus, ExistenceRequirement, Time, fungibles, tokens::DepositConsequence}, 
+		ensure, transactional, BoundedVec, PalletId};
+	use frame_support::sp_runtime::{traits::{AccountIdConversion, CheckedSub, Hash, SaturatedConversion, Saturating, Zero}, ArithmeticError, Perbill, Perquintill, TransactionOutcome};
+	use frame_support::storage::with_transaction;
+	use frame_system::{pallet_prelude::{OriginFor, BlockNumberFor}, ensure_signed, ensure_root};
 	use sp_io::hashing::blake2_128;
+	use sp_std::vec::Vec;
+	use pallet_token::AssetFactory;
 
 	use frame_support::log;
 
@@ -31,8 +38,12 @@ pub mod pallet {
 
 	pub use crate::weights::*;
 
+	/// bump on every change to the layout of a stored type, with a migration in `migrations`
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	pub(crate) type BalanceOf<T> = <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
@@ -41,6 +52,17 @@ pub mod pallet {
 
 	pub(crate) type TimeOf<T> = <<T as Config>::KittyTime as frame_support::traits::Time>::Moment;
 
+	pub type KittyInfoOf<T> = KittyInfo<AccountOf<T>, BalanceOf<T>, TimeOf<T>, <T as Config>::AssetId>;
+
+	/// maximum number of kitties returned by a single `listed_kitties` query
+	pub const MAX_KITTIES_PER_PAGE: u32 = 100;
+
+	/// maximum number of generations walked by a single `ancestry` query
+	pub const MAX_ANCESTRY_DEPTH: u32 = 8;
+
+	/// scale of the staking reward accumulator, keeps the remainder of small rewards over large weights
+	pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
@@ -48,12 +70,73 @@ pub mod pallet {
 
 		type KittyRandomness: Randomness<<Self as frame_system::Config>::Hash, <Self as frame_system::Config>::BlockNumber>;
 
-		type Currency: Currency<Self::AccountId>;
+		type Currency: ReservableCurrency<Self::AccountId>;
 
 		type MaxOwnerKitty: Get<u32>;
 
+		type MaxAuctionDuration: Get<Self::BlockNumber>;
+
+		type MaxDutchDuration: Get<Self::BlockNumber>;
+
+		type MaxAuctionsPerBlock: Get<u32>;
+
+		type MaxOffers: Get<u32>;
+
+		type MaxOffersPerBlock: Get<u32>;
+
+		type CreatorRoyalty: Get<Perbill>;
+
+		type MarketplaceFee: Get<Perbill>;
+
+		type FeeDestination: Get<Self::AccountId>;
+
 		type KittyTime: Time;
 
+		type MaxNameLength: Get<u32>;
+
+		type MaxMetadataLength: Get<u32>;
+
+		// reserved for every byte of name and metadata
+		type MetadataDepositPerByte: Get<BalanceOf<Self>>;
+
+		type MaxChildren: Get<u32>;
+
+		// age at which a kitten becomes an adult and can be listed
+		type AdultAge: Get<TimeOf<Self>>;
+
+		type ElderAge: Get<TimeOf<Self>>;
+
+		// time after a transfer before the kitty can change hands again
+		type ActionCooldown: Get<TimeOf<Self>>;
+
+		type MaxLeaseDuration: Get<Self::BlockNumber>;
+
+		type MaxLeasesPerBlock: Get<u32>;
+
+		// swap proposals a single kitty can be part of
+		type MaxSwaps: Get<u32>;
+
+		type MaxSwapsPerBlock: Get<u32>;
+
+		type MaxBundleSize: Get<u32>;
+
+		type AssetId: Member + Parameter + Copy + MaxEncodedLen;
+
+		// fungible assets kitties can be priced in
+		type Assets: fungibles::Transfer<Self::AccountId, AssetId = Self::AssetId, Balance = BalanceOf<Self>>;
+
+		// origin allowed to change the accepted assets
+		type AssetAdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		// vaults of fractionalized kitties are derived from it
+		type PalletId: Get<PalletId>;
+
+		// creates the shares of fractionalized kitties and the staking rewards
+		type Tokens: AssetFactory<Self::AccountId, AssetId = Self::AssetId, Balance = BalanceOf<Self>>;
+
+		// longest gap between updates of the staking reward pool, bounds the reward rate
+		type MaxBlocksBetweenUpdates: Get<Self::BlockNumber>;
+
 		type WeightInfo: WeightInfo;
 
 	}
@@ -62,13 +145,192 @@ pub mod pallet {
 	#[pallet::getter(fn count)]
 	pub type KittyCounter<T> = StorageValue<_, u32, ValueQuery, >;
 
+	// index given to the next kitty, indexes of burned kitties are never reused
+	#[pallet::storage]
+	#[pallet::getter(fn next_kitty_index)]
+	pub type NextKittyIndex<T> = StorageValue<_, KittyIndex, ValueQuery, >;
+
 	#[pallet::storage]
 	#[pallet::getter(fn kitties)]
-	pub type Kitties<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, Kitty<T>, OptionQuery, >;
+	pub type Kitties<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, Kitty<T>, OptionQuery, >;
 
 	#[pallet::storage]
 	#[pallet::getter(fn kitty_owner)]
-	pub type KittyOwner<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<T::Hash, T::MaxOwnerKitty>, ValueQuery, >;
+	pub type KittyOwner<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, BoundedVec<KittyIndex, T::MaxOwnerKitty>, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn auctions)]
+	pub type Auctions<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, Auction<AccountOf<T>, BalanceOf<T>, T::BlockNumber>, OptionQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn dutch_listings)]
+	pub type DutchListings<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, DutchListing<BalanceOf<T>, T::BlockNumber>, OptionQuery, >;
+
+	// auctions to settle at the given block
+	#[pallet::storage]
+	#[pallet::getter(fn auctions_ending)]
+	pub type AuctionsEnding<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<KittyIndex, T::MaxAuctionsPerBlock>, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn offers)]
+	pub type Offers<T: Config> = StorageDoubleMap<_, Blake2_128Concat, KittyIndex, Blake2_128Concat, T::AccountId, Offer<BalanceOf<T>, T::BlockNumber>, OptionQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_offer_count)]
+	pub type KittyOfferCount<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, u32, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn account_offer_count)]
+	pub type AccountOfferCount<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery, >;
+
+	// royalty lowered by the creator of the kitty
+	#[pallet::storage]
+	#[pallet::getter(fn royalty_override)]
+	pub type RoyaltyOverride<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, Perbill, OptionQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_approval)]
+	pub type KittyApprovals<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, T::AccountId, OptionQuery, >;
+
+	// operators allowed to move every kitty of an owner
+	#[pallet::storage]
+	#[pallet::getter(fn is_approved_for_all)]
+	pub type OperatorApprovals<T: Config> = StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, T::AccountId, bool, ValueQuery, >;
+
+	// offers to expire at the given block
+	#[pallet::storage]
+	#[pallet::getter(fn offers_expiring)]
+	pub type OffersExpiring<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<(KittyIndex, T::AccountId), T::MaxOffersPerBlock>, ValueQuery, >;
+
+	// metadata of a kitty and the deposit reserved for it and the name
+	#[pallet::storage]
+	#[pallet::getter(fn metadata)]
+	pub type Metadata<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, KittyMetadata<AccountOf<T>, BalanceOf<T>, BoundedVec<u8, T::MaxMetadataLength>>, OptionQuery, >;
+
+	// earliest time a kitty can be transferred or sold again
+	#[pallet::storage]
+	#[pallet::getter(fn next_action_at)]
+	pub type NextActionAt<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, TimeOf<T>, OptionQuery, >;
+
+	// male kitties offered for breeding with their fee
+	#[pallet::storage]
+	#[pallet::getter(fn sire_offers)]
+	pub type SireOffers<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, BalanceOf<T>, OptionQuery, >;
+
+	// leases proposed by the owner, waiting for the user to accept
+	#[pallet::storage]
+	#[pallet::getter(fn lease_offers)]
+	pub type LeaseOffers<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, LeaseOffer<AccountOf<T>, BalanceOf<T>, T::BlockNumber>, OptionQuery, >;
+
+	// the user of a kitty, separate from its owner, until the lease expires
+	#[pallet::storage]
+	#[pallet::getter(fn leases)]
+	pub type Leases<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, Lease<AccountOf<T>, T::BlockNumber>, OptionQuery, >;
+
+	// leases to end at the given block
+	#[pallet::storage]
+	#[pallet::getter(fn leases_expiring)]
+	pub type LeasesExpiring<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<KittyIndex, T::MaxLeasesPerBlock>, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_swap_id)]
+	pub type NextSwapId<T> = StorageValue<_, SwapId, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn swaps)]
+	pub type Swaps<T: Config> = StorageMap<_, Blake2_128Concat, SwapId, Swap<AccountOf<T>, BalanceOf<T>, T::BlockNumber>, OptionQuery, >;
+
+	// swaps a kitty is offered or wanted in
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_swaps)]
+	pub type KittySwaps<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, BoundedVec<SwapId, T::MaxSwaps>, ValueQuery, >;
+
+	// swaps to expire at the given block
+	#[pallet::storage]
+	#[pallet::getter(fn swaps_expiring)]
+	pub type SwapsExpiring<T: Config> = StorageMap<_, Twox64Concat, T::BlockNumber, BoundedVec<SwapId, T::MaxSwapsPerBlock>, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_bundle_id)]
+	pub type NextBundleId<T> = StorageValue<_, BundleId, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn bundles)]
+	pub type Bundles<T: Config> = StorageMap<_, Blake2_128Concat, BundleId, Bundle<AccountOf<T>, BalanceOf<T>, BoundedVec<KittyIndex, T::MaxBundleSize>>, OptionQuery, >;
+
+	// the bundle a kitty is listed in
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_bundle)]
+	pub type KittyBundle<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, BundleId, OptionQuery, >;
+
+	// assets accepted as payment for kitties
+	#[pallet::storage]
+	#[pallet::getter(fn is_accepted_asset)]
+	pub type AcceptedAssets<T: Config> = StorageMap<_, Blake2_128Concat, T::AssetId, bool, ValueQuery, >;
+
+	// fractionalized kitties by the asset of their shares
+	#[pallet::storage]
+	#[pallet::getter(fn vaults)]
+	pub type Vaults<T: Config> = StorageMap<_, Blake2_128Concat, T::AssetId, Vault<BalanceOf<T>>, OptionQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_vault)]
+	pub type KittyVault<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, T::AssetId, OptionQuery, >;
+
+	// asset minted as staking rewards
+	#[pallet::storage]
+	#[pallet::getter(fn reward_asset)]
+	pub type RewardAsset<T: Config> = StorageValue<_, T::AssetId, OptionQuery, >;
+
+	// rewards minted per block, shared by all staked kitties
+	#[pallet::storage]
+	#[pallet::getter(fn reward_rate)]
+	pub type RewardRate<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn total_stake_weight)]
+	pub type TotalStakeWeight<T> = StorageValue<_, u64, ValueQuery, >;
+
+	// rewards earned by a single unit of weight since genesis, scaled by `REWARD_PRECISION`
+	#[pallet::storage]
+	#[pallet::getter(fn reward_per_weight)]
+	pub type RewardPerWeight<T> = StorageValue<_, u128, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn last_reward_block)]
+	pub type LastRewardBlock<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn stakes)]
+	pub type Stakes<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, Stake<AccountOf<T>>, OptionQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_loan_id)]
+	pub type NextLoanId<T> = StorageValue<_, LoanId, ValueQuery, >;
+
+	#[pallet::storage]
+	#[pallet::getter(fn loans)]
+	pub type Loans<T: Config> = StorageMap<_, Blake2_128Concat, LoanId, Loan<AccountOf<T>, BalanceOf<T>, T::BlockNumber>, OptionQuery, >;
+
+	// the loan a kitty is collateral for
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_loan)]
+	pub type KittyLoan<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, LoanId, OptionQuery, >;
+
+	// kitties bred from a kitty
+	#[pallet::storage]
+	#[pallet::getter(fn children)]
+	pub type Children<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, BoundedVec<KittyIndex, T::MaxChildren>, ValueQuery, >;
+
+	// the former hash id of every kitty
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_id_by_hash)]
+	pub type KittyIdByHash<T: Config> = StorageMap<_, Blake2_128Concat, T::Hash, KittyIndex, OptionQuery, >;
+
+	// the hash id of every kitty, to clear `KittyIdByHash` on burn
+	#[pallet::storage]
+	#[pallet::getter(fn kitty_hash)]
+	pub type KittyHash<T: Config> = StorageMap<_, Blake2_128Concat, KittyIndex, T::Hash, OptionQuery, >;
 
 
 	// set genesis config for kitty owner
@@ -96,17 +358,9 @@ pub mod pallet {
 
 				let kitty = Kitty::new(owner.clone(), *dna, gender, T::KittyTime::now());
 
-				let kitty_id = T::Hashing::hash_of(&kitty);
-
-				<Kitties<T>>::insert(kitty_id.clone(), kitty);
-
-				let _ok_or_error = <KittyOwner<T>>::try_mutate(&owner, |kitty_vec| {
-					kitty_vec.try_push(kitty_id)
-				}).map_err(|_| <Error<T>>::KittyNotExists);
+				<Pallet<T>>::insert_kitty(owner, kitty).expect("genesis kitties are valid");
 
 			}
-			let kitty_counter: u32 = self.kitty_owner.len().try_into().unwrap();
-			<KittyCounter<T>>::put(kitty_counter);
 		}
 	} 
 	
@@ -114,9 +368,62 @@ pub mod pallet {
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		KittyCreated(AccountOf<T>, T::Hash),
-		KittyTranfered(T::Hash, AccountOf<T>),
-		PriceSet(T::Hash, Option<BalanceOf<T>>),
+		KittyCreated(AccountOf<T>, KittyIndex),
+		KittyTranfered(KittyIndex, AccountOf<T>),
+		PriceSet(KittyIndex, Option<(PriceCurrency<T::AssetId>, BalanceOf<T>)>),
+		KittySold(KittyIndex, AccountOf<T>, AccountOf<T>, BalanceOf<T>, BalanceOf<T>),
+		KittyBred(AccountOf<T>, KittyIndex, KittyIndex, KittyIndex),
+		AuctionCreated(KittyIndex, AccountOf<T>, BalanceOf<T>, T::BlockNumber),
+		AuctionBid(KittyIndex, AccountOf<T>, BalanceOf<T>),
+		AuctionBidRefunded(KittyIndex, AccountOf<T>, BalanceOf<T>),
+		AuctionSettled(KittyIndex, AccountOf<T>, BalanceOf<T>),
+		AuctionEnded(KittyIndex),
+		DutchListed(KittyIndex, BalanceOf<T>, BalanceOf<T>, T::BlockNumber),
+		DutchListingCancelled(KittyIndex),
+		OfferMade(KittyIndex, AccountOf<T>, BalanceOf<T>, T::BlockNumber),
+		OfferAccepted(KittyIndex, AccountOf<T>, BalanceOf<T>),
+		OfferWithdrawn(KittyIndex, AccountOf<T>),
+		OfferExpired(KittyIndex, AccountOf<T>),
+		RoyaltySet(KittyIndex, Perbill),
+		MarketplaceFeePaid(KittyIndex, AccountOf<T>, BalanceOf<T>),
+		MarketplaceFeeSkipped(KittyIndex, AccountOf<T>, BalanceOf<T>),
+		KittyApproved(KittyIndex, AccountOf<T>),
+		ApprovalCancelled(KittyIndex),
+		ApprovalForAll(AccountOf<T>, AccountOf<T>, bool),
+		KittyBurned(AccountOf<T>, KittyIndex),
+		MetadataSet(KittyIndex, BalanceOf<T>),
+		MetadataCleared(KittyIndex),
+		SireOffered(KittyIndex, BalanceOf<T>),
+		SireOfferCancelled(KittyIndex),
+		BredWithSire(AccountOf<T>, KittyIndex, KittyIndex, KittyIndex, BalanceOf<T>),
+		LeaseOffered(KittyIndex, AccountOf<T>, BalanceOf<T>, T::BlockNumber),
+		LeaseOfferCancelled(KittyIndex),
+		KittyLeased(KittyIndex, AccountOf<T>, BalanceOf<T>, T::BlockNumber),
+		LeaseEnded(KittyIndex, AccountOf<T>),
+		SwapProposed(SwapId, AccountOf<T>, KittyIndex, KittyIndex, Option<BalanceOf<T>>, T::BlockNumber),
+		SwapAccepted(SwapId, AccountOf<T>),
+		SwapCancelled(SwapId),
+		SwapExpired(SwapId),
+		SwapInvalidated(SwapId),
+		BundleListed(BundleId, AccountOf<T>, BalanceOf<T>),
+		BundleCancelled(BundleId),
+		BundleSold(BundleId, AccountOf<T>, AccountOf<T>, BalanceOf<T>),
+		AcceptedAssetSet(T::AssetId, bool),
+		KittySoldForAsset(KittyIndex, AccountOf<T>, AccountOf<T>, T::AssetId, BalanceOf<T>, BalanceOf<T>),
+		KittyFractionalized(KittyIndex, AccountOf<T>, T::AssetId, BalanceOf<T>, BalanceOf<T>),
+		KittyRedeemed(KittyIndex, AccountOf<T>, T::AssetId),
+		KittyBoughtOut(KittyIndex, AccountOf<T>, T::AssetId, BalanceOf<T>),
+		BuyoutClaimed(T::AssetId, AccountOf<T>, BalanceOf<T>),
+		RewardAssetCreated(T::AssetId),
+		RewardRateSet(BalanceOf<T>),
+		KittyStaked(KittyIndex, AccountOf<T>, u32),
+		KittyUnstaked(KittyIndex, AccountOf<T>),
+		RewardsClaimed(KittyIndex, AccountOf<T>, BalanceOf<T>),
+		LoanRequested(LoanId, AccountOf<T>, KittyIndex, BalanceOf<T>, BalanceOf<T>, T::BlockNumber),
+		LoanCancelled(LoanId),
+		LoanFunded(LoanId, AccountOf<T>, T::BlockNumber),
+		LoanRepaid(LoanId, AccountOf<T>, BalanceOf<T>),
+		CollateralClaimed(LoanId, AccountOf<T>, KittyIndex),
 	}
 
 	#[pallet::error]
@@ -129,6 +436,140 @@ pub mod pallet {
 		MaxOwnerKitty,
 		KittyPriceNone,
 		NotEnoughMoney,
+		BuyerWouldBeReaped,
+		SameParent,
+		SameGender,
+		KittyInAuction,
+		AuctionNotExists,
+		AuctionExpired,
+		InvalidAuctionDuration,
+		TooManyAuctionsEnding,
+		SellerCannotBid,
+		BidBelowReserve,
+		BidTooLow,
+		KittyInDutchListing,
+		DutchListingNotExists,
+		InvalidDutchListing,
+		OfferOnOwnKitty,
+		OfferTooLow,
+		OfferExists,
+		OfferNotExists,
+		OfferExpired,
+		TooManyOffers,
+		TooManyOffersExpiring,
+		NotKittyCreator,
+		RoyaltyTooHigh,
+		NotApproved,
+		ApproveToSelf,
+		KittyExists,
+		KittyHashCollision,
+		KittyIndexOverflow,
+		NameTooLong,
+		MetadataTooLong,
+		MetadataNotExists,
+		TooManyChildren,
+		KittyTooYoung,
+		KittyInCooldown,
+		SireNotMale,
+		SireNotOffered,
+		SireOwnedByBreeder,
+		MatronNotFemale,
+		KittyLeased,
+		LeaseToSelf,
+		InvalidLeaseDuration,
+		LeaseOfferNotExists,
+		LeaseOfferExists,
+		LeaseFeeTooHigh,
+		TooManyLeasesExpiring,
+		SwapWithSelf,
+		SwapNotExists,
+		SwapExpired,
+		NotSwapProposer,
+		TooManySwaps,
+		TooManySwapsExpiring,
+		SwapIdOverflow,
+		KittyInBundle,
+		EmptyBundle,
+		DuplicateKittyInBundle,
+		BundleNotExists,
+		NotBundleSeller,
+		BundleIdOverflow,
+		AssetNotAccepted,
+		KittyFractionalized,
+		InvalidShares,
+		InvalidBuyoutPrice,
+		VaultNotExists,
+		VaultBoughtOut,
+		VaultNotBoughtOut,
+		NotAllShares,
+		NoShares,
+		KittyStaked,
+		KittyNotStaked,
+		NotStaker,
+		KittyInLoan,
+		InvalidLoanDuration,
+		LoanNotExists,
+		NotBorrower,
+		NotLender,
+		LendToSelf,
+		LoanFunded,
+		LoanNotFunded,
+		LoanNotDefaulted,
+		LoanIdOverflow,
+		RewardRateTooHigh,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let ending = <AuctionsEnding<T>>::take(now);
+
+			let expiring = <OffersExpiring<T>>::take(now);
+
+			let leases_expiring = <LeasesExpiring<T>>::take(now);
+
+			let swaps_expiring = <SwapsExpiring<T>>::take(now);
+
+			let mut weight = T::DbWeight::get().reads_writes(5, 4);
+
+			for kitty_id in ending {
+				Self::settle_auction(kitty_id);
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(6, 6));
+			}
+
+			for (kitty_id, bidder) in expiring {
+				Self::expire_offer(kitty_id, bidder, now);
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(3, 3));
+			}
+
+			for kitty_id in leases_expiring {
+				Self::end_lease(kitty_id, now);
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+			}
+
+			for swap_id in swaps_expiring {
+				if Self::remove_swap(swap_id, true).is_some() {
+					Self::deposit_event(Event::SwapExpired(swap_id));
+				}
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(3, 3));
+			}
+
+			// the reward rate is only checked against gaps up to the bound
+			if now.saturating_sub(Self::last_reward_block()) >= T::MaxBlocksBetweenUpdates::get() {
+				Self::update_reward_pool();
+				weight = weight.saturating_add(T::DbWeight::get().reads_writes(3, 2));
+			}
+
+			weight
+		}
+
+		fn integrity_test() {
+			// the royalty and the fee are both taken out of the sale price
+			assert!(
+				T::CreatorRoyalty::get().deconstruct() + T::MarketplaceFee::get().deconstruct() <= Perbill::one().deconstruct(),
+				"CreatorRoyalty and MarketplaceFee must not add up to more than the sale price",
+			);
+		}
 	}
 
 	#[pallet::call]
@@ -140,19 +581,7 @@ pub mod pallet {
 
 			let kitty = Self::mint(who.clone());
 
-			let current_kitty_count = Self::count();
-
-			<KittyCounter<T>>::put(current_kitty_count + 1);
-
-			let kitty_id = T::Hashing::hash_of(&kitty);
-
-			<Kitties<T>>::insert(kitty_id.clone(), kitty.clone());
-
-			let mut kitty_owner = Self::kitty_owner(&who);
-
-			kitty_owner.try_push(kitty_id.clone()).map_err(|_| <Error<T>>::MaxOwnerKitty)?;
-
-			<KittyOwner<T>>::insert(who.clone(), kitty_owner.clone());
+			let kitty_id = Self::insert_kitty(&who, kitty.clone())?;
 
 			log::info!("Create new Kitty: {:?}", kitty);
 
@@ -162,16 +591,28 @@ pub mod pallet {
 		}
 		
 		#[pallet::weight(10_000)]
-		pub fn set_price(origin: OriginFor<T>, kitty_id: T::Hash, price: Option<BalanceOf<T>>) -> DispatchResult {
+		pub fn set_price(origin: OriginFor<T>, kitty_id: KittyIndex, price: Option<(PriceCurrency<T::AssetId>, BalanceOf<T>)>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			
 			ensure!(Self::is_kitty_owner(&kitty_id, &who)?, <Error<T>>::NotKittyOwner);
 
+			Self::ensure_not_locked(&kitty_id)?;
+
+			ensure!(!<DutchListings<T>>::contains_key(&kitty_id), <Error<T>>::KittyInDutchListing);
+
 			let mut kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
 
+			if let Some((currency, _)) = price {
+				if let PriceCurrency::Asset(asset_id) = currency {
+					ensure!(Self::is_accepted_asset(asset_id), <Error<T>>::AssetNotAccepted);
+				}
+
+				Self::ensure_adult(&kitty)?;
+			}
+
 			kitty.set_price(price);
 
-			<Kitties<T>>::insert(kitty_id.clone(), kitty);
+			<Kitties<T>>::insert(kitty_id, kitty);
 
 			Self::deposit_event(<Event<T>>::PriceSet(kitty_id, price));
 
@@ -181,7 +622,7 @@ pub mod pallet {
 
 		#[transactional]
 		#[pallet::weight(10_000)]
-		pub fn transfer(origin: OriginFor<T>, to: T::AccountId, kitty_id: T::Hash) -> DispatchResult {
+		pub fn transfer(origin: OriginFor<T>, to: T::AccountId, kitty_id: KittyIndex) -> DispatchResult {
 			let from = ensure_signed(origin)?;
 
 			// ensure the kitty owner
@@ -190,91 +631,1860 @@ pub mod pallet {
 			// ensure not transfer to self
 			ensure!(from != to, <Error<T>>::TransferToSelf);
 
+			Self::ensure_not_locked(&kitty_id)?;
+
+			Self::ensure_cooled_down(&kitty_id)?;
+
 			Self::transfer_to(kitty_id, &to)?;
 
+			Self::deposit_event(Event::KittyTranfered(kitty_id, to));
+
 			Ok(())
 		}
 
 		#[transactional]
 		#[pallet::weight(10_000)]
-		pub fn buy(origin: OriginFor<T>, kitty_id: T::Hash) -> DispatchResult {
+		pub fn buy(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
 
 			let buyer = ensure_signed(origin)?;
 
 			let kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
 
-			let kitty_price = kitty.price().ok_or(<Error<T>>::KittyPriceNone)?;
+			Self::ensure_not_locked(&kitty_id)?;
 
-			// ensure buyer has enough money
-			ensure!(T::Currency::free_balance(&buyer) >= kitty_price, <Error<T>>::NotEnoughMoney);
+			Self::ensure_cooled_down(&kitty_id)?;
+
+			let (kitty_price, payment) = if let Some(listing) = Self::dutch_listings(&kitty_id) {
+				(listing.price_at(<frame_system::Pallet<T>>::block_number()), Payment::Currency)
+			} else {
+				match kitty.price().ok_or(<Error<T>>::KittyPriceNone)? {
+					(PriceCurrency::Native, price) => (price, Payment::Currency),
+					(PriceCurrency::Asset(asset_id), price) => {
+						// the asset may have been removed from the accepted ones since the listing
+						ensure!(Self::is_accepted_asset(asset_id), <Error<T>>::AssetNotAccepted);
+
+						(price, Payment::Asset(asset_id))
+					},
+				}
+			};
 
 			let owner = kitty.owner();
 
 			// ensure not transfer to self
 			ensure!(owner != buyer, <Error<T>>::TransferToSelf);
 
-			Self::transfer_to(kitty_id, &buyer)?;
+			Self::sell(kitty_id, &owner, &buyer, kitty_price, payment)?;
 
 			Ok(())
 		}
 
-	}
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn breed(origin: OriginFor<T>, parent_a: KittyIndex, parent_b: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
 
-	
-	/// helper 
-	impl <T:Config> Pallet<T> {
+			ensure!(parent_a != parent_b, <Error<T>>::SameParent);
 
-		fn generate_dna() -> [u8; 16] {
+			ensure!(Self::is_kitty_owner(&parent_a, &who)?, <Error<T>>::NotKittyOwner);
+			ensure!(Self::is_kitty_owner(&parent_b, &who)?, <Error<T>>::NotKittyOwner);
 
-			let payload = (
-				T::KittyRandomness::random(&b"dna"[..]).0,
-				<frame_system::Pallet<T>>::extrinsic_index().unwrap_or_default(),
-				<frame_system::Pallet<T>>::block_number(),
-			);
-			payload.using_encoded(blake2_128)
+			let kitty_a = Self::kitties(&parent_a).ok_or(<Error<T>>::KittyNotExists)?;
+			let kitty_b = Self::kitties(&parent_b).ok_or(<Error<T>>::KittyNotExists)?;
+
+			// ensure one MALE and one FEMALE parent
+			ensure!(kitty_a.gender() != kitty_b.gender(), <Error<T>>::SameGender);
+
+			let kitty_id = Self::breed_child(&who, (parent_a, &kitty_a), (parent_b, &kitty_b))?;
+
+			Self::deposit_event(Event::KittyBred(who, kitty_id, parent_a, parent_b));
+
+			Ok(())
 		}
 
-		fn generate_gender(dna: &[u8; 16] ) -> Gender {
-			if dna[0] % 2 == 0 {
-				return Gender::MALE;
-			}
-			Gender::FEMALE
+		#[pallet::weight(10_000)]
+		pub fn offer_sire(origin: OriginFor<T>, sire_id: KittyIndex, fee: BalanceOf<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&sire_id, &who)?, <Error<T>>::NotKittyOwner);
+
+			let sire = Self::kitties(&sire_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			ensure!(sire.gender() == Gender::MALE, <Error<T>>::SireNotMale);
+
+			Self::ensure_adult(&sire)?;
+
+			<SireOffers<T>>::insert(sire_id, fee);
+
+			Self::deposit_event(Event::SireOffered(sire_id, fee));
+
+			Ok(())
 		}
 
-		fn mint(who: T::AccountId) -> Kitty<T> {
-			let dna = Self::generate_dna();
-			let gender = Self::generate_gender(&dna);
-			let now = T::KittyTime::now();
-			<Kitty<T>>::new(who, dna, gender, now)
+		#[pallet::weight(10_000)]
+		pub fn cancel_sire_offer(origin: OriginFor<T>, sire_id: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&sire_id, &who)?, <Error<T>>::NotKittyOwner);
+
+			ensure!(<SireOffers<T>>::contains_key(&sire_id), <Error<T>>::SireNotOffered);
+
+			<SireOffers<T>>::remove(&sire_id);
+
+			Self::deposit_event(Event::SireOfferCancelled(sire_id));
+
+			Ok(())
 		}
 
-		fn is_kitty_owner(kitty_id: &T::Hash, owner: &T::AccountId) -> Result<bool, Error<T>> {
-			match Self::kitties(kitty_id) {
-				Some(kitty) => Ok(kitty.owner() == *owner),
-				None => Err(Error::KittyNotExists),
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn breed_with_sire(origin: OriginFor<T>, matron_id: KittyIndex, sire_id: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&matron_id, &who)?, <Error<T>>::NotKittyOwner);
+
+			let fee = Self::sire_offers(&sire_id).ok_or(<Error<T>>::SireNotOffered)?;
+
+			let matron = Self::kitties(&matron_id).ok_or(<Error<T>>::KittyNotExists)?;
+			let sire = Self::kitties(&sire_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			let sire_owner = sire.owner();
+
+			// the owner breeds its own kitties for free
+			ensure!(sire_owner != who, <Error<T>>::SireOwnedByBreeder);
+
+			ensure!(matron.gender() == Gender::FEMALE, <Error<T>>::MatronNotFemale);
+
+			let kitty_id = Self::breed_child(&who, (matron_id, &matron), (sire_id, &sire))?;
+
+			if !fee.is_zero() {
+				Self::pay(&who, &sire_owner, fee)?;
 			}
+
+			Self::deposit_event(Event::BredWithSire(who, kitty_id, matron_id, sire_id, fee));
+
+			Ok(())
 		}
 
-		fn transfer_to(kitty_id: T::Hash, to: &T::AccountId) -> Result<(), Error<T>> { 
+		#[pallet::weight(10_000)]
+		pub fn create_auction(origin: OriginFor<T>, kitty_id: KittyIndex, reserve: BalanceOf<T>, duration: T::BlockNumber) -> DispatchResult {
+			let seller = ensure_signed(origin)?;
 
-			let mut kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+			ensure!(Self::is_kitty_owner(&kitty_id, &seller)?, <Error<T>>::NotKittyOwner);
 
-			let owner = kitty.owner();
+			Self::ensure_not_locked(&kitty_id)?;
 
-			// remove old kitty owner
-			<KittyOwner<T>>::try_mutate(&owner, |kitty_vec| {
-				if let Some(position) = kitty_vec.iter().position(|x| *x == kitty_id.clone()) {
-					kitty_vec.swap_remove(position);
-					return Ok(());
+			ensure!(!duration.is_zero() && duration <= T::MaxAuctionDuration::get(), <Error<T>>::InvalidAuctionDuration);
+
+			let kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			Self::ensure_adult(&kitty)?;
+
+			Self::ensure_cooled_down(&kitty_id)?;
+
+			let end = <frame_system::Pallet<T>>::block_number().saturating_add(duration);
+
+			<AuctionsEnding<T>>::try_mutate(end, |kitty_vec| {
+				kitty_vec.try_push(kitty_id)
+			}).map_err(|_| <Error<T>>::TooManyAuctionsEnding)?;
+
+			// a kitty in auction can not be bought at a fixed price
+			<Kitties<T>>::mutate(&kitty_id, |kitty| {
+				if let Some(kitty) = kitty {
+					kitty.set_price(None);
 				}
-				Err(())
-			}).map_err(|_| <Error<T>>::KittyNotExists)?;
+			});
+			<DutchListings<T>>::remove(&kitty_id);
 
-			// update kitty owner
+			<Auctions<T>>::insert(kitty_id, Auction {
+				seller: seller.clone(),
+				reserve,
+				end,
+				highest_bid: None,
+			});
 
-			kitty.set_price(None);
-			kitty.set_owner(to.clone());
-			<Kitties<T>>::insert(kitty_id.clone(), kitty);
+			Self::deposit_event(Event::AuctionCreated(kitty_id, seller, reserve, end));
+
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn bid(origin: OriginFor<T>, kitty_id: KittyIndex, amount: BalanceOf<T>) -> DispatchResult {
+			let bidder = ensure_signed(origin)?;
+
+			let mut auction = Self::auctions(&kitty_id).ok_or(<Error<T>>::AuctionNotExists)?;
+
+			ensure!(<frame_system::Pallet<T>>::block_number() < auction.end, <Error<T>>::AuctionExpired);
+
+			ensure!(bidder != auction.seller, <Error<T>>::SellerCannotBid);
+
+			ensure!(amount >= auction.reserve, <Error<T>>::BidBelowReserve);
+
+			// refund the outbid bidder
+			if let Some((highest_bidder, highest_amount)) = auction.highest_bid.take() {
+				ensure!(amount > highest_amount, <Error<T>>::BidTooLow);
+
+				T::Currency::unreserve(&highest_bidder, highest_amount);
+
+				Self::deposit_event(Event::AuctionBidRefunded(kitty_id, highest_bidder, highest_amount));
+			}
+
+			T::Currency::reserve(&bidder, amount)?;
+
+			auction.highest_bid = Some((bidder.clone(), amount));
+
+			<Auctions<T>>::insert(kitty_id, auction);
+
+			Self::deposit_event(Event::AuctionBid(kitty_id, bidder, amount));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn list_dutch(origin: OriginFor<T>, kitty_id: KittyIndex, start_price: BalanceOf<T>, floor_price: BalanceOf<T>, duration: T::BlockNumber) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &who)?, <Error<T>>::NotKittyOwner);
+
+			Self::ensure_not_locked(&kitty_id)?;
+
+			ensure!(!duration.is_zero() && duration <= T::MaxDutchDuration::get(), <Error<T>>::InvalidDutchListing);
+
+			ensure!(start_price >= floor_price, <Error<T>>::InvalidDutchListing);
+
+			let kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			Self::ensure_adult(&kitty)?;
+
+			// the dutch listing replaces the fixed price
+			<Kitties<T>>::mutate(&kitty_id, |kitty| {
+				if let Some(kitty) = kitty {
+					kitty.set_price(None);
+				}
+			});
+
+			<DutchListings<T>>::insert(kitty_id, DutchListing {
+				start_price,
+				floor_price,
+				start: <frame_system::Pallet<T>>::block_number(),
+				duration,
+			});
+
+			Self::deposit_event(Event::DutchListed(kitty_id, start_price, floor_price, duration));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn cancel_dutch_listing(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &who)?, <Error<T>>::NotKittyOwner);
+
+			ensure!(<DutchListings<T>>::contains_key(&kitty_id), <Error<T>>::DutchListingNotExists);
+
+			<DutchListings<T>>::remove(&kitty_id);
+
+			Self::deposit_event(Event::DutchListingCancelled(kitty_id));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn make_offer(origin: OriginFor<T>, kitty_id: KittyIndex, amount: BalanceOf<T>, expiry: T::BlockNumber) -> DispatchResult {
+			let bidder = ensure_signed(origin)?;
+
+			ensure!(!Self::is_kitty_owner(&kitty_id, &bidder)?, <Error<T>>::OfferOnOwnKitty);
+
+			ensure!(expiry > <frame_system::Pallet<T>>::block_number(), <Error<T>>::OfferExpired);
+
+			// free offers would take the offer slots of a kitty at no cost
+			ensure!(amount >= T::Currency::minimum_balance(), <Error<T>>::OfferTooLow);
+
+			ensure!(!<Offers<T>>::contains_key(&kitty_id, &bidder), <Error<T>>::OfferExists);
+
+			ensure!(Self::kitty_offer_count(&kitty_id) < T::MaxOffers::get(), <Error<T>>::TooManyOffers);
+			ensure!(Self::account_offer_count(&bidder) < T::MaxOffers::get(), <Error<T>>::TooManyOffers);
+
+			<OffersExpiring<T>>::try_mutate(expiry, |offer_vec| {
+				offer_vec.try_push((kitty_id, bidder.clone()))
+			}).map_err(|_| <Error<T>>::TooManyOffersExpiring)?;
+
+			T::Currency::reserve(&bidder, amount)?;
+
+			<Offers<T>>::insert(&kitty_id, &bidder, Offer { amount, expiry });
+			<KittyOfferCount<T>>::mutate(&kitty_id, |count| *count += 1);
+			<AccountOfferCount<T>>::mutate(&bidder, |count| *count += 1);
+
+			Self::deposit_event(Event::OfferMade(kitty_id, bidder, amount, expiry));
+
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn accept_offer(origin: OriginFor<T>, kitty_id: KittyIndex, bidder: T::AccountId) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &owner)?, <Error<T>>::NotKittyOwner);
+
+			Self::ensure_not_locked(&kitty_id)?;
+
+			Self::ensure_cooled_down(&kitty_id)?;
+
+			let offer = Self::take_offer(&kitty_id, &bidder).ok_or(<Error<T>>::OfferNotExists)?;
+
+			ensure!(<frame_system::Pallet<T>>::block_number() < offer.expiry, <Error<T>>::OfferExpired);
+
+			Self::sell(kitty_id, &owner, &bidder, offer.amount, Payment::Reserved)?;
+
+			Self::deposit_event(Event::OfferAccepted(kitty_id, bidder, offer.amount));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn withdraw_offer(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
+			let bidder = ensure_signed(origin)?;
+
+			let offer = Self::take_offer(&kitty_id, &bidder).ok_or(<Error<T>>::OfferNotExists)?;
+
+			T::Currency::unreserve(&bidder, offer.amount);
+
+			Self::deposit_event(Event::OfferWithdrawn(kitty_id, bidder));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn set_royalty(origin: OriginFor<T>, kitty_id: KittyIndex, royalty: Perbill) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			ensure!(kitty.creator() == who, <Error<T>>::NotKittyCreator);
+
+			// the creator can lower the royalty but never raise it
+			ensure!(royalty <= Self::royalty_of(&kitty_id), <Error<T>>::RoyaltyTooHigh);
+
+			<RoyaltyOverride<T>>::insert(kitty_id, royalty);
+
+			Self::deposit_event(Event::RoyaltySet(kitty_id, royalty));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn approve(origin: OriginFor<T>, kitty_id: KittyIndex, operator: T::AccountId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let owner = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?.owner();
+
+			// the owner or one of its operators can approve
+			ensure!(who == owner || Self::is_approved_for_all(&owner, &who), <Error<T>>::NotApproved);
+
+			ensure!(operator != owner, <Error<T>>::ApproveToSelf);
+
+			<KittyApprovals<T>>::insert(kitty_id, operator.clone());
+
+			Self::deposit_event(Event::KittyApproved(kitty_id, operator));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn cancel_approval(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let owner = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?.owner();
+
+			ensure!(who == owner || Self::is_approved_for_all(&owner, &who), <Error<T>>::NotApproved);
+
+			<KittyApprovals<T>>::remove(kitty_id);
+
+			Self::deposit_event(Event::ApprovalCancelled(kitty_id));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn set_approval_for_all(origin: OriginFor<T>, operator: T::AccountId, approved: bool) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			ensure!(operator != owner, <Error<T>>::ApproveToSelf);
+
+			if approved {
+				<OperatorApprovals<T>>::insert(&owner, &operator, true);
+			} else {
+				<OperatorApprovals<T>>::remove(&owner, &operator);
+			}
+
+			Self::deposit_event(Event::ApprovalForAll(owner, operator, approved));
+
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn transfer_from(origin: OriginFor<T>, from: T::AccountId, to: T::AccountId, kitty_id: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &from)?, <Error<T>>::NotKittyOwner);
+
+			ensure!(Self::is_approved_or_owner(&kitty_id, &from, &who), <Error<T>>::NotApproved);
+
+			// ensure not transfer to self
+			ensure!(from != to, <Error<T>>::TransferToSelf);
+
+			Self::ensure_not_locked(&kitty_id)?;
+
+			Self::ensure_cooled_down(&kitty_id)?;
+
+			Self::transfer_to(kitty_id, &to)?;
+
+			Self::deposit_event(Event::KittyTranfered(kitty_id, to));
+
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn burn(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &who)?, <Error<T>>::NotKittyOwner);
+
+			Self::do_burn(kitty_id)
+		}
+
+		/// burn any kitty, its auction, bundle, lease, stake, loan or vault is ended first
+		///
+		/// the highest auction bid is refunded and stake rewards are paid, a lender loses the collateral
+		/// and share holders of a vault keep shares of nothing
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn force_burn(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
+			ensure_root(origin)?;
+
+			Self::unlock(kitty_id)?;
+
+			Self::do_burn(kitty_id)
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn set_kitty_metadata(origin: OriginFor<T>, kitty_id: KittyIndex, name: Vec<u8>, data: Vec<u8>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &who)?, <Error<T>>::NotKittyOwner);
+
+			let name: BoundedVec<u8, T::MaxNameLength> = name.try_into().map_err(|_| <Error<T>>::NameTooLong)?;
+			let data: BoundedVec<u8, T::MaxMetadataLength> = data.try_into().map_err(|_| <Error<T>>::MetadataTooLong)?;
+
+			let bytes = (name.len() + data.len()) as u32;
+			let deposit = T::MetadataDepositPerByte::get().saturating_mul(bytes.into());
+
+			// the previous deposit is refunded before the new one is taken
+			Self::release_metadata(&kitty_id);
+
+			T::Currency::reserve(&who, deposit)?;
+
+			let mut kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+			kitty.set_name(name);
+			<Kitties<T>>::insert(kitty_id, kitty);
+
+			<Metadata<T>>::insert(kitty_id, KittyMetadata {
+				depositor: who,
+				deposit,
+				data,
+			});
+
+			Self::deposit_event(Event::MetadataSet(kitty_id, deposit));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn lease(origin: OriginFor<T>, kitty_id: KittyIndex, to: T::AccountId, duration: T::BlockNumber, fee: BalanceOf<T>) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &owner)?, <Error<T>>::NotKittyOwner);
+
+			ensure!(owner != to, <Error<T>>::LeaseToSelf);
+
+			Self::ensure_not_locked(&kitty_id)?;
+
+			// the standing offer must be cancelled before another one is made
+			ensure!(!<LeaseOffers<T>>::contains_key(&kitty_id), <Error<T>>::LeaseOfferExists);
+
+			let kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			Self::ensure_adult(&kitty)?;
+
+			ensure!(!duration.is_zero() && duration <= T::MaxLeaseDuration::get(), <Error<T>>::InvalidLeaseDuration);
+
+			<LeaseOffers<T>>::insert(kitty_id, LeaseOffer {
+				to: to.clone(),
+				fee,
+				duration,
+			});
+
+			Self::deposit_event(Event::LeaseOffered(kitty_id, to, fee, duration));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn cancel_lease_offer(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &owner)?, <Error<T>>::NotKittyOwner);
+
+			ensure!(<LeaseOffers<T>>::contains_key(&kitty_id), <Error<T>>::LeaseOfferNotExists);
+
+			<LeaseOffers<T>>::remove(&kitty_id);
+
+			Self::deposit_event(Event::LeaseOfferCancelled(kitty_id));
+
+			Ok(())
+		}
+
+		/// start the lease offered to the caller, paying at most `max_fee` to the owner
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn accept_lease(origin: OriginFor<T>, kitty_id: KittyIndex, max_fee: BalanceOf<T>) -> DispatchResult {
+			let user = ensure_signed(origin)?;
+
+			let offer = match Self::lease_offers(&kitty_id) {
+				Some(offer) if offer.to == user => offer,
+				_ => return Err(<Error<T>>::LeaseOfferNotExists.into()),
+			};
+
+			// the owner may have raised the fee after the offer was seen
+			ensure!(offer.fee <= max_fee, <Error<T>>::LeaseFeeTooHigh);
+
+			Self::ensure_not_locked(&kitty_id)?;
+
+			let mut kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			let expiry = <frame_system::Pallet<T>>::block_number().saturating_add(offer.duration);
+
+			<LeasesExpiring<T>>::try_mutate(expiry, |kitty_vec| {
+				kitty_vec.try_push(kitty_id)
+			}).map_err(|_| <Error<T>>::TooManyLeasesExpiring)?;
+
+			if !offer.fee.is_zero() {
+				Self::pay(&user, &kitty.owner(), offer.fee)?;
+			}
+
+			// a leased kitty can not be sold
+			kitty.set_price(None);
+			<Kitties<T>>::insert(kitty_id, kitty);
+			<DutchListings<T>>::remove(&kitty_id);
+
+			<LeaseOffers<T>>::remove(&kitty_id);
+			<Leases<T>>::insert(kitty_id, Lease {
+				user: user.clone(),
+				expiry,
+			});
+
+			Self::deposit_event(Event::KittyLeased(kitty_id, user, offer.fee, expiry));
+
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn propose_swap(origin: OriginFor<T>, my_kitty: KittyIndex, their_kitty: KittyIndex, top_up: Option<BalanceOf<T>>, expiry: T::BlockNumber) -> DispatchResult {
+			let proposer = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&my_kitty, &proposer)?, <Error<T>>::NotKittyOwner);
+
+			ensure!(!Self::is_kitty_owner(&their_kitty, &proposer)?, <Error<T>>::SwapWithSelf);
+
+			ensure!(expiry > <frame_system::Pallet<T>>::block_number(), <Error<T>>::SwapExpired);
+
+			// a swap that can not be accepted would only take up an expiry slot
+			for kitty_id in [my_kitty, their_kitty] {
+				Self::ensure_not_locked(&kitty_id)?;
+
+				Self::ensure_cooled_down(&kitty_id)?;
+			}
+
+			let swap_id = Self::next_swap_id();
+
+			<NextSwapId<T>>::put(swap_id.checked_add(1).ok_or(<Error<T>>::SwapIdOverflow)?);
+
+			for kitty_id in [my_kitty, their_kitty] {
+				<KittySwaps<T>>::try_mutate(kitty_id, |swap_vec| {
+					swap_vec.try_push(swap_id)
+				}).map_err(|_| <Error<T>>::TooManySwaps)?;
+			}
+
+			<SwapsExpiring<T>>::try_mutate(expiry, |swap_vec| {
+				swap_vec.try_push(swap_id)
+			}).map_err(|_| <Error<T>>::TooManySwapsExpiring)?;
+
+			if let Some(amount) = top_up {
+				T::Currency::reserve(&proposer, amount)?;
+			}
+
+			<Swaps<T>>::insert(swap_id, Swap {
+				proposer: proposer.clone(),
+				offered: my_kitty,
+				wanted: their_kitty,
+				top_up,
+				expiry,
+			});
+
+			Self::deposit_event(Event::SwapProposed(swap_id, proposer, my_kitty, their_kitty, top_up, expiry));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn cancel_swap(origin: OriginFor<T>, swap_id: SwapId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let swap = Self::swaps(&swap_id).ok_or(<Error<T>>::SwapNotExists)?;
+
+			ensure!(swap.proposer == who, <Error<T>>::NotSwapProposer);
+
+			Self::remove_swap(swap_id, true);
+
+			Self::deposit_event(Event::SwapCancelled(swap_id));
+
+			Ok(())
+		}
+
+		/// hand the wanted kitty to the proposer and the offered kitty and top up to the caller
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn accept_swap(origin: OriginFor<T>, swap_id: SwapId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let swap = Self::swaps(&swap_id).ok_or(<Error<T>>::SwapNotExists)?;
+
+			ensure!(<frame_system::Pallet<T>>::block_number() < swap.expiry, <Error<T>>::SwapExpired);
+
+			ensure!(Self::is_kitty_owner(&swap.wanted, &who)?, <Error<T>>::NotKittyOwner);
+			ensure!(Self::is_kitty_owner(&swap.offered, &swap.proposer)?, <Error<T>>::NotKittyOwner);
+
+			for kitty_id in [swap.offered, swap.wanted] {
+				Self::ensure_not_locked(&kitty_id)?;
+				Self::ensure_cooled_down(&kitty_id)?;
+			}
+
+			Self::remove_swap(swap_id, false);
+
+			if let Some(amount) = swap.top_up {
+				Self::pay_reserved(&swap.proposer, &who, amount)?;
+			}
+
+			// both kitties leave their owners first, so a full account can still swap
+			Self::detach_from_owner(swap.offered)?;
+			Self::detach_from_owner(swap.wanted)?;
+			Self::attach_to_owner(swap.offered, &who)?;
+			Self::attach_to_owner(swap.wanted, &swap.proposer)?;
+
+			Self::deposit_event(Event::SwapAccepted(swap_id, who.clone()));
+			Self::deposit_event(Event::KittyTranfered(swap.offered, who));
+			Self::deposit_event(Event::KittyTranfered(swap.wanted, swap.proposer));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn list_bundle(origin: OriginFor<T>, kitty_ids: BoundedVec<KittyIndex, T::MaxBundleSize>, price: BalanceOf<T>) -> DispatchResult {
+			let seller = ensure_signed(origin)?;
+
+			ensure!(!kitty_ids.is_empty(), <Error<T>>::EmptyBundle);
+
+			for (i, kitty_id) in kitty_ids.iter().enumerate() {
+				ensure!(!kitty_ids[..i].contains(kitty_id), <Error<T>>::DuplicateKittyInBundle);
+
+				ensure!(Self::is_kitty_owner(kitty_id, &seller)?, <Error<T>>::NotKittyOwner);
+
+				Self::ensure_not_locked(kitty_id)?;
+
+				let kitty = Self::kitties(kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+				Self::ensure_adult(&kitty)?;
+			}
+
+			let bundle_id = Self::next_bundle_id();
+
+			<NextBundleId<T>>::put(bundle_id.checked_add(1).ok_or(<Error<T>>::BundleIdOverflow)?);
+
+			// kitties in a bundle are only sold with it
+			for kitty_id in kitty_ids.iter() {
+				<Kitties<T>>::mutate(kitty_id, |kitty| {
+					if let Some(kitty) = kitty {
+						kitty.set_price(None);
+					}
+				});
+				<DutchListings<T>>::remove(kitty_id);
+				<KittyBundle<T>>::insert(kitty_id, bundle_id);
+			}
+
+			<Bundles<T>>::insert(bundle_id, Bundle {
+				seller: seller.clone(),
+				kitties: kitty_ids,
+				price,
+			});
+
+			Self::deposit_event(Event::BundleListed(bundle_id, seller, price));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn cancel_bundle(origin: OriginFor<T>, bundle_id: BundleId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let bundle = Self::bundles(&bundle_id).ok_or(<Error<T>>::BundleNotExists)?;
+
+			ensure!(bundle.seller == who, <Error<T>>::NotBundleSeller);
+
+			Self::remove_bundle(bundle_id);
+
+			Self::deposit_event(Event::BundleCancelled(bundle_id));
+
+			Ok(())
+		}
+
+		/// buy every kitty of a bundle, the price is split evenly between them for royalties and fees
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn buy_bundle(origin: OriginFor<T>, bundle_id: BundleId) -> DispatchResult {
+			let buyer = ensure_signed(origin)?;
+
+			let bundle = Self::remove_bundle(bundle_id).ok_or(<Error<T>>::BundleNotExists)?;
+
+			// ensure not transfer to self
+			ensure!(bundle.seller != buyer, <Error<T>>::TransferToSelf);
+
+			let count = bundle.kitties.len() as u32;
+
+			// fail before any kitty is moved when the buyer can not hold all of them
+			ensure!(Self::kitty_owner(&buyer).len() as u32 + count <= T::MaxOwnerKitty::get(), <Error<T>>::MaxOwnerKitty);
+
+			let share = bundle.price / BalanceOf::<T>::from(count);
+
+			for (i, kitty_id) in bundle.kitties.iter().enumerate() {
+				Self::ensure_not_locked(kitty_id)?;
+
+				Self::ensure_cooled_down(kitty_id)?;
+
+				// the last kitty carries the rounding remainder
+				let price = if i as u32 + 1 == count {
+					bundle.price - share * BalanceOf::<T>::from(count - 1)
+				} else {
+					share
+				};
+
+				Self::sell(*kitty_id, &bundle.seller, &buyer, price, Payment::Currency)?;
+			}
+
+			Self::deposit_event(Event::BundleSold(bundle_id, bundle.seller, buyer, bundle.price));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn set_accepted_asset(origin: OriginFor<T>, asset_id: T::AssetId, accepted: bool) -> DispatchResult {
+			T::AssetAdminOrigin::ensure_origin(origin)?;
+
+			if accepted {
+				<AcceptedAssets<T>>::insert(asset_id, true);
+			} else {
+				<AcceptedAssets<T>>::remove(asset_id);
+			}
+
+			Self::deposit_event(Event::AcceptedAssetSet(asset_id, accepted));
+
+			Ok(())
+		}
+
+		/// lock the kitty in a vault and mint `shares` of a new asset to the caller
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn fractionalize(origin: OriginFor<T>, kitty_id: KittyIndex, shares: BalanceOf<T>, buyout_price: BalanceOf<T>) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &owner)?, <Error<T>>::NotKittyOwner);
+
+			Self::ensure_not_locked(&kitty_id)?;
+
+			Self::ensure_cooled_down(&kitty_id)?;
+
+			ensure!(!shares.is_zero(), <Error<T>>::InvalidShares);
+
+			// the buyout price is paid into the empty vault account, so it has to create it
+			ensure!(buyout_price >= T::Currency::minimum_balance(), <Error<T>>::InvalidBuyoutPrice);
+
+			let asset_id = T::Tokens::create_asset(&Self::account_id(), 1_u32.into())?;
+
+			Self::transfer_to(kitty_id, &Self::vault_account(asset_id))?;
+
+			T::Tokens::mint(asset_id, &owner, shares)?;
+
+			<KittyVault<T>>::insert(kitty_id, asset_id);
+			<Vaults<T>>::insert(asset_id, Vault {
+				kitty_id,
+				shares,
+				buyout_price,
+				bought_out: false,
+			});
+
+			Self::deposit_event(Event::KittyFractionalized(kitty_id, owner, asset_id, shares, buyout_price));
+
+			Ok(())
+		}
+
+		/// burn every share of a vault to take its kitty out
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn redeem(origin: OriginFor<T>, asset_id: T::AssetId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let vault = Self::vaults(&asset_id).ok_or(<Error<T>>::VaultNotExists)?;
+
+			ensure!(!vault.bought_out, <Error<T>>::VaultBoughtOut);
+
+			ensure!(T::Tokens::balance(asset_id, &who) == vault.shares, <Error<T>>::NotAllShares);
+
+			T::Tokens::burn(asset_id, &who, vault.shares)?;
+
+			<Vaults<T>>::remove(&asset_id);
+			<KittyVault<T>>::remove(&vault.kitty_id);
+
+			Self::transfer_to(vault.kitty_id, &who)?;
+
+			Self::deposit_event(Event::KittyRedeemed(vault.kitty_id, who, asset_id));
+
+			Ok(())
+		}
+
+		/// pay the buyout price into the vault and take the kitty
+		///
+		/// share holders are not paid here, each of them calls `claim_buyout` for their part
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn buyout(origin: OriginFor<T>, asset_id: T::AssetId) -> DispatchResult {
+			let buyer = ensure_signed(origin)?;
+
+			let mut vault = Self::vaults(&asset_id).ok_or(<Error<T>>::VaultNotExists)?;
+
+			ensure!(!vault.bought_out, <Error<T>>::VaultBoughtOut);
+
+			Self::pay(&buyer, &Self::vault_account(asset_id), vault.buyout_price)?;
+
+			<KittyVault<T>>::remove(&vault.kitty_id);
+
+			Self::transfer_to(vault.kitty_id, &buyer)?;
+
+			vault.bought_out = true;
+			<Vaults<T>>::insert(asset_id, vault.clone());
+
+			Self::deposit_event(Event::KittyBoughtOut(vault.kitty_id, buyer, asset_id, vault.buyout_price));
+
+			Ok(())
+		}
+
+		/// burn the caller's shares of a bought out vault for their part of the buyout price
+		///
+		/// the part is pro rata to the shares left in the vault, the last holder also takes the rounding
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn claim_buyout(origin: OriginFor<T>, asset_id: T::AssetId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut vault = Self::vaults(&asset_id).ok_or(<Error<T>>::VaultNotExists)?;
+
+			ensure!(vault.bought_out, <Error<T>>::VaultNotBoughtOut);
+
+			let shares = T::Tokens::balance(asset_id, &who);
+
+			ensure!(!shares.is_zero(), <Error<T>>::NoShares);
+
+			let vault_account = Self::vault_account(asset_id);
+			let funds = T::Currency::free_balance(&vault_account);
+
+			// the last holder takes whatever rounding left in the vault
+			let amount = if shares >= vault.shares {
+				funds
+			} else {
+				Perquintill::from_rational(shares, vault.shares) * funds
+			};
+
+			T::Tokens::burn(asset_id, &who, shares)?;
+
+			T::Currency::transfer(&vault_account, &who, amount, ExistenceRequirement::AllowDeath)?;
+
+			vault.shares = vault.shares.saturating_sub(shares);
+
+			if vault.shares.is_zero() {
+				<Vaults<T>>::remove(&asset_id);
+			} else {
+				<Vaults<T>>::insert(asset_id, vault);
+			}
+
+			Self::deposit_event(Event::BuyoutClaimed(asset_id, who, amount));
+
+			Ok(())
+		}
+
+		/// the reward asset is created with the first rate, which is bounded by `MaxBlocksBetweenUpdates`
+		#[pallet::weight(10_000)]
+		pub fn set_reward_rate(origin: OriginFor<T>, rate: BalanceOf<T>) -> DispatchResult {
+			ensure_root(origin)?;
+
+			// the rewards of the longest gap between pool updates must fit the accumulator
+			let max_blocks: u128 = T::MaxBlocksBetweenUpdates::get().saturated_into();
+			let max_rewards = rate.saturated_into::<u128>().checked_mul(max_blocks).and_then(|rewards| rewards.checked_mul(REWARD_PRECISION));
+
+			ensure!(max_rewards.is_some(), <Error<T>>::RewardRateTooHigh);
+
+			// rewards until now are earned at the previous rate
+			Self::update_reward_pool();
+
+			if Self::reward_asset().is_none() {
+				let asset_id = T::Tokens::create_asset(&Self::account_id(), 1_u32.into())?;
+
+				<RewardAsset<T>>::put(asset_id);
+
+				Self::deposit_event(Event::RewardAssetCreated(asset_id));
+			}
+
+			<RewardRate<T>>::put(rate);
+
+			Self::deposit_event(Event::RewardRateSet(rate));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn stake(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &who)?, <Error<T>>::NotKittyOwner);
+
+			Self::ensure_not_locked(&kitty_id)?;
+
+			let mut kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			let weight = kitty.traits().rarity_score;
+
+			Self::update_reward_pool();
+
+			// a staked kitty can not be sold
+			kitty.set_price(None);
+			<Kitties<T>>::insert(kitty_id, kitty);
+			<DutchListings<T>>::remove(&kitty_id);
+
+			<Stakes<T>>::insert(kitty_id, Stake {
+				staker: who.clone(),
+				weight,
+				reward_debt: (weight as u128).saturating_mul(Self::reward_per_weight()),
+			});
+			<TotalStakeWeight<T>>::mutate(|total| *total = total.saturating_add(weight as u64));
+
+			Self::deposit_event(Event::KittyStaked(kitty_id, who, weight));
+
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn unstake(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut stake = Self::stakes(&kitty_id).ok_or(<Error<T>>::KittyNotStaked)?;
+
+			ensure!(stake.staker == who, <Error<T>>::NotStaker);
+
+			Self::update_reward_pool();
+
+			Self::pay_rewards(kitty_id, &mut stake)?;
+
+			<Stakes<T>>::remove(&kitty_id);
+			<TotalStakeWeight<T>>::mutate(|total| *total = total.saturating_sub(stake.weight as u64));
+
+			Self::deposit_event(Event::KittyUnstaked(kitty_id, who));
+
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn claim_rewards(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut stake = Self::stakes(&kitty_id).ok_or(<Error<T>>::KittyNotStaked)?;
+
+			ensure!(stake.staker == who, <Error<T>>::NotStaker);
+
+			Self::update_reward_pool();
+
+			Self::pay_rewards(kitty_id, &mut stake)?;
+
+			<Stakes<T>>::insert(kitty_id, stake);
+
+			Ok(())
+		}
+
+		/// lock the kitty as collateral for a loan of `principal`, to repay with `interest` within `duration` blocks once funded
+		#[pallet::weight(10_000)]
+		pub fn request_loan(origin: OriginFor<T>, kitty_id: KittyIndex, principal: BalanceOf<T>, interest: BalanceOf<T>, duration: T::BlockNumber) -> DispatchResult {
+			let borrower = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &borrower)?, <Error<T>>::NotKittyOwner);
+
+			Self::ensure_not_locked(&kitty_id)?;
+
+			ensure!(!duration.is_zero(), <Error<T>>::InvalidLoanDuration);
+
+			let loan_id = Self::next_loan_id();
+
+			<NextLoanId<T>>::put(loan_id.checked_add(1).ok_or(<Error<T>>::LoanIdOverflow)?);
+
+			// the collateral can not be sold
+			<Kitties<T>>::mutate(&kitty_id, |kitty| {
+				if let Some(kitty) = kitty {
+					kitty.set_price(None);
+				}
+			});
+			<DutchListings<T>>::remove(&kitty_id);
+
+			<KittyLoan<T>>::insert(kitty_id, loan_id);
+			<Loans<T>>::insert(loan_id, Loan {
+				borrower: borrower.clone(),
+				kitty_id,
+				principal,
+				interest,
+				duration,
+				funding: None,
+			});
+
+			Self::deposit_event(Event::LoanRequested(loan_id, borrower, kitty_id, principal, interest, duration));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn cancel_loan_request(origin: OriginFor<T>, loan_id: LoanId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let loan = Self::loans(&loan_id).ok_or(<Error<T>>::LoanNotExists)?;
+
+			ensure!(loan.borrower == who, <Error<T>>::NotBorrower);
+
+			ensure!(loan.funding.is_none(), <Error<T>>::LoanFunded);
+
+			Self::remove_loan(loan_id, &loan);
+
+			Self::deposit_event(Event::LoanCancelled(loan_id));
+
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn fund_loan(origin: OriginFor<T>, loan_id: LoanId) -> DispatchResult {
+			let lender = ensure_signed(origin)?;
+
+			let mut loan = Self::loans(&loan_id).ok_or(<Error<T>>::LoanNotExists)?;
+
+			ensure!(loan.funding.is_none(), <Error<T>>::LoanFunded);
+
+			ensure!(loan.borrower != lender, <Error<T>>::LendToSelf);
+
+			Self::pay(&lender, &loan.borrower, loan.principal)?;
+
+			let deadline = <frame_system::Pallet<T>>::block_number().saturating_add(loan.duration);
+
+			loan.funding = Some((lender.clone(), deadline));
+			<Loans<T>>::insert(loan_id, loan);
+
+			Self::deposit_event(Event::LoanFunded(loan_id, lender, deadline));
+
+			Ok(())
+		}
+
+		/// pay back the principal and interest to unlock the kitty, possible until the lender claims it
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn repay_loan(origin: OriginFor<T>, loan_id: LoanId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let loan = Self::loans(&loan_id).ok_or(<Error<T>>::LoanNotExists)?;
+
+			ensure!(loan.borrower == who, <Error<T>>::NotBorrower);
+
+			let (lender, _) = loan.funding.clone().ok_or(<Error<T>>::LoanNotFunded)?;
+
+			let amount = loan.principal.saturating_add(loan.interest);
+
+			Self::pay(&who, &lender, amount)?;
+
+			Self::remove_loan(loan_id, &loan);
+
+			Self::deposit_event(Event::LoanRepaid(loan_id, lender, amount));
+
+			Ok(())
+		}
+
+		#[transactional]
+		#[pallet::weight(10_000)]
+		pub fn claim_collateral(origin: OriginFor<T>, loan_id: LoanId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let loan = Self::loans(&loan_id).ok_or(<Error<T>>::LoanNotExists)?;
+
+			let (lender, deadline) = loan.funding.clone().ok_or(<Error<T>>::LoanNotFunded)?;
+
+			ensure!(lender == who, <Error<T>>::NotLender);
+
+			ensure!(<frame_system::Pallet<T>>::block_number() > deadline, <Error<T>>::LoanNotDefaulted);
+
+			Self::remove_loan(loan_id, &loan);
+
+			Self::transfer_to(loan.kitty_id, &lender)?;
+
+			Self::deposit_event(Event::CollateralClaimed(loan_id, lender.clone(), loan.kitty_id));
+			Self::deposit_event(Event::KittyTranfered(loan.kitty_id, lender));
+
+			Ok(())
+		}
+
+		#[pallet::weight(10_000)]
+		pub fn clear_kitty_metadata(origin: OriginFor<T>, kitty_id: KittyIndex) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(Self::is_kitty_owner(&kitty_id, &who)?, <Error<T>>::NotKittyOwner);
+
+			ensure!(<Metadata<T>>::contains_key(&kitty_id), <Error<T>>::MetadataNotExists);
+
+			Self::release_metadata(&kitty_id);
+
+			<Kitties<T>>::mutate(&kitty_id, |kitty| {
+				if let Some(kitty) = kitty {
+					kitty.set_name(Default::default());
+				}
+			});
+
+			Self::deposit_event(Event::MetadataCleared(kitty_id));
+
+			Ok(())
+		}
+
+	}
+
+	
+	/// helper 
+	impl <T:Config> Pallet<T> {
+
+		/// the next kitty index keeps kitties minted in the same extrinsic apart
+		fn generate_dna() -> [u8; 16] {
+
+			let payload = (
+				T::KittyRandomness::random(&b"dna"[..]).0,
+				<frame_system::Pallet<T>>::extrinsic_index().unwrap_or_default(),
+				<frame_system::Pallet<T>>::block_number(),
+				Self::next_kitty_index(),
+			);
+			payload.using_encoded(blake2_128)
+		}
+
+		/// copy every byte of the child DNA whole from one of the parents, using a random selector
+		pub(crate) fn mix_dna(dna_a: &[u8; 16], dna_b: &[u8; 16]) -> [u8; 16] {
+
+			let selector = (
+				T::KittyRandomness::random(&b"breed"[..]).0,
+				<frame_system::Pallet<T>>::extrinsic_index().unwrap_or_default(),
+				<frame_system::Pallet<T>>::block_number(),
+				Self::next_kitty_index(),
+			).using_encoded(blake2_128);
+
+			let mut dna = [0u8; 16];
+			for (i, gene) in dna.iter_mut().enumerate() {
+				// traits are decoded per byte, so bytes are never split between the parents
+				*gene = if selector[i] & 1 == 0 { dna_a[i] } else { dna_b[i] };
+			}
+			dna
+		}
+
+		fn generate_gender(dna: &[u8; 16] ) -> Gender {
+			if dna[0] % 2 == 0 {
+				return Gender::MALE;
+			}
+			Gender::FEMALE
+		}
+
+		/// mint the child of two unlocked, adult and rested kitties to `owner`, the parents then rest
+		fn breed_child(owner: &T::AccountId, parent_a: (KittyIndex, &Kitty<T>), parent_b: (KittyIndex, &Kitty<T>)) -> Result<KittyIndex, Error<T>> {
+			let (parent_a, kitty_a) = parent_a;
+			let (parent_b, kitty_b) = parent_b;
+
+			for (parent, kitty) in [(parent_a, kitty_a), (parent_b, kitty_b)] {
+				Self::ensure_not_locked(&parent)?;
+
+				Self::ensure_adult(kitty)?;
+
+				Self::ensure_cooled_down(&parent)?;
+			}
+
+			let dna = Self::mix_dna(&kitty_a.dna(), &kitty_b.dna());
+			let gender = Self::generate_gender(&dna);
+
+			let mut kitty = <Kitty<T>>::new(owner.clone(), dna, gender, T::KittyTime::now());
+			kitty.set_parents((parent_a, parent_b));
+			kitty.set_generation(kitty_a.generation().max(kitty_b.generation()).saturating_add(1));
+
+			let kitty_id = Self::insert_kitty(owner, kitty)?;
+
+			for parent in [parent_a, parent_b] {
+				<Children<T>>::try_mutate(parent, |kitty_vec| {
+					kitty_vec.try_push(kitty_id)
+				}).map_err(|_| <Error<T>>::TooManyChildren)?;
+			}
+
+			let next_action_at = T::KittyTime::now().saturating_add(T::ActionCooldown::get());
+			<NextActionAt<T>>::insert(parent_a, next_action_at);
+			<NextActionAt<T>>::insert(parent_b, next_action_at);
+
+			Ok(kitty_id)
+		}
+
+		pub(crate) fn mint(who: T::AccountId) -> Kitty<T> {
+			let dna = Self::generate_dna();
+			let gender = Self::generate_gender(&dna);
+			let now = T::KittyTime::now();
+			<Kitty<T>>::new(who, dna, gender, now)
+		}
+
+		fn insert_kitty(owner: &T::AccountId, kitty: Kitty<T>) -> Result<KittyIndex, Error<T>> {
+
+			let kitty_id = Self::next_kitty_index();
+
+			Self::insert_kitty_with_id(kitty_id, owner, kitty)?;
+
+			Ok(kitty_id)
+		}
+
+		pub(crate) fn insert_kitty_with_id(kitty_id: KittyIndex, owner: &T::AccountId, mut kitty: Kitty<T>) -> Result<(), Error<T>> {
+
+			// indexes below the next one belong to existing or burned kitties
+			ensure!(kitty_id >= Self::next_kitty_index(), <Error<T>>::KittyExists);
+
+			let next_kitty_id = kitty_id.checked_add(1).ok_or(<Error<T>>::KittyIndexOverflow)?;
+
+			// the hash is taken before the index is set, two kitties with the same content collide
+			let kitty_hash = T::Hashing::hash_of(&kitty);
+
+			ensure!(!<KittyIdByHash<T>>::contains_key(&kitty_hash), <Error<T>>::KittyHashCollision);
+
+			kitty.set_index(kitty_id);
+
+			<KittyOwner<T>>::try_mutate(owner, |kitty_vec| {
+				kitty_vec.try_push(kitty_id)
+			}).map_err(|_| <Error<T>>::MaxOwnerKitty)?;
+
+			<Kitties<T>>::insert(kitty_id, kitty);
+			<KittyIdByHash<T>>::insert(kitty_hash, kitty_id);
+			<KittyHash<T>>::insert(kitty_id, kitty_hash);
+			<NextKittyIndex<T>>::put(next_kitty_id);
+
+			<KittyCounter<T>>::put(Self::count() + 1);
+
+			Ok(())
+		}
+
+		fn is_approved_or_owner(kitty_id: &KittyIndex, owner: &T::AccountId, who: &T::AccountId) -> bool {
+			who == owner
+				|| Self::kitty_approval(kitty_id).as_ref() == Some(who)
+				|| Self::is_approved_for_all(owner, who)
+		}
+
+		pub(crate) fn is_kitty_owner(kitty_id: &KittyIndex, owner: &T::AccountId) -> Result<bool, Error<T>> {
+			match Self::kitties(kitty_id) {
+				Some(kitty) => Ok(kitty.owner() == *owner),
+				None => Err(Error::KittyNotExists),
+			}
+		}
+
+		/// move `amount` from the buyer to the seller, never reaping the buyer's account
+		fn pay(buyer: &T::AccountId, seller: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			let free_balance = T::Currency::free_balance(buyer);
+
+			// ensure buyer has enough money
+			ensure!(free_balance >= amount, <Error<T>>::NotEnoughMoney);
+
+			// ensure buyer stays above the existential deposit
+			ensure!(free_balance - amount >= T::Currency::minimum_balance(), <Error<T>>::BuyerWouldBeReaped);
+
+			T::Currency::transfer(buyer, seller, amount, ExistenceRequirement::KeepAlive)
+		}
+
+		/// move `amount` of an asset from the buyer to the seller, never reaping the buyer's asset account
+		fn pay_asset(asset_id: T::AssetId, buyer: &T::AccountId, seller: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			let reducible_balance = <T::Assets as fungibles::Inspect<T::AccountId>>::reducible_balance(asset_id, buyer, true);
+
+			ensure!(reducible_balance >= amount, <Error<T>>::NotEnoughMoney);
+
+			<T::Assets as fungibles::Transfer<T::AccountId>>::transfer(asset_id, buyer, seller, amount, true)?;
+
+			Ok(())
+		}
+
+		/// move `amount` out of the buyer's reserved balance to the seller
+		fn pay_reserved(buyer: &T::AccountId, seller: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			// reserved funds can only be repatriated to an existing account
+			if T::Currency::total_balance(seller).is_zero() {
+				T::Currency::unreserve(buyer, amount);
+
+				return Self::pay(buyer, seller, amount);
+			}
+
+			let remaining = T::Currency::repatriate_reserved(buyer, seller, amount, BalanceStatus::Free)?;
+
+			ensure!(remaining.is_zero(), <Error<T>>::NotEnoughMoney);
+
+			Ok(())
+		}
+
+		/// pay the seller and hand the kitty to the buyer, `payment` tells where the price is taken from
+		fn sell(kitty_id: KittyIndex, seller: &T::AccountId, buyer: &T::AccountId, price: BalanceOf<T>, payment: Payment<T::AssetId>) -> DispatchResult {
+			let kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			let creator = kitty.creator();
+
+			let mut royalty = Self::royalty_of(&kitty_id) * price;
+
+			// the seller keeps the royalty on its own kitty, or when it can not be paid to a reaped creator
+			if creator == *seller || !Self::can_receive(&creator, royalty, payment) {
+				royalty = Zero::zero();
+			}
+
+			let fee_destination = T::FeeDestination::get();
+
+			let mut fee = T::MarketplaceFee::get() * price;
+
+			// the seller keeps a fee the fee destination can not receive
+			if !fee.is_zero() && !Self::can_receive(&fee_destination, fee, payment) {
+				Self::deposit_event(Event::MarketplaceFeeSkipped(kitty_id, fee_destination.clone(), fee));
+
+				fee = Zero::zero();
+			}
+
+			let pay_to = |to: &T::AccountId, amount: BalanceOf<T>| -> DispatchResult {
+				match payment {
+					Payment::Currency => Self::pay(buyer, to, amount),
+					Payment::Reserved => Self::pay_reserved(buyer, to, amount),
+					Payment::Asset(asset_id) => Self::pay_asset(asset_id, buyer, to, amount),
+				}
+			};
+
+			if !royalty.is_zero() {
+				if creator != *buyer {
+					pay_to(&creator, royalty)?;
+				} else if payment == Payment::Reserved {
+					// the creator buying back its kitty keeps the royalty
+					T::Currency::unreserve(buyer, royalty);
+				}
+			}
+
+			if !fee.is_zero() {
+				pay_to(&fee_destination, fee)?;
+
+				Self::deposit_event(Event::MarketplaceFeePaid(kitty_id, fee_destination, fee));
+			}
+
+			let proceeds = price.checked_sub(&royalty)
+				.and_then(|rest| rest.checked_sub(&fee))
+				.ok_or(ArithmeticError::Underflow)?;
+
+			pay_to(seller, proceeds)?;
+
+			Self::transfer_to(kitty_id, buyer)?;
+
+			match payment {
+				Payment::Asset(asset_id) => {
+					Self::deposit_event(Event::KittySoldForAsset(kitty_id, seller.clone(), buyer.clone(), asset_id, price, royalty));
+				},
+				_ => {
+					Self::deposit_event(Event::KittySold(kitty_id, seller.clone(), buyer.clone(), price, royalty));
+				},
+			}
+
+			Ok(())
+		}
+
+		pub fn kitty_info(kitty_id: &KittyIndex) -> Option<KittyInfoOf<T>> {
+			Self::kitties(kitty_id).map(|kitty| Self::to_info(*kitty_id, kitty))
+		}
+
+		pub fn kitty_traits(kitty_id: &KittyIndex) -> Option<KittyTraits> {
+			Self::kitties(kitty_id).map(|kitty| kitty.traits())
+		}
+
+		/// ancestors of a kitty with their distance to it, closest first, up to `MAX_ANCESTRY_DEPTH` generations
+		pub fn ancestry(kitty_id: &KittyIndex, depth: u32) -> Vec<(KittyIndex, u32)> {
+			let mut ancestors: Vec<(KittyIndex, u32)> = Vec::new();
+			let mut current = sp_std::vec![*kitty_id];
+
+			for distance in 1..=depth.min(MAX_ANCESTRY_DEPTH) {
+				let mut parents = Vec::new();
+
+				for parent in current.iter().filter_map(|id| Self::kitties(id)?.parents()) {
+					for id in [parent.0, parent.1] {
+						// kitties bred from relatives share ancestors
+						if !ancestors.iter().any(|(ancestor, _)| *ancestor == id) {
+							ancestors.push((id, distance));
+							parents.push(id);
+						}
+					}
+				}
+
+				if parents.is_empty() {
+					break;
+				}
+				current = parents;
+			}
+
+			ancestors
+		}
+
+		pub fn kitty_info_by_hash(kitty_hash: &T::Hash) -> Option<KittyInfoOf<T>> {
+			Self::kitty_id_by_hash(kitty_hash).and_then(|kitty_id| Self::kitty_info(&kitty_id))
+		}
+
+		pub fn kitties_of(owner: &T::AccountId) -> Vec<KittyInfoOf<T>> {
+			Self::kitty_owner(owner)
+				.into_iter()
+				.filter_map(|kitty_id| Self::kitty_info(&kitty_id))
+				.collect()
+		}
+
+		/// kitties for sale at a fixed price, in an accepted asset or in a dutch listing
+		pub fn listed_kitties(offset: u32, limit: u32) -> Vec<KittyInfoOf<T>> {
+			<Kitties<T>>::iter()
+				.map(|(kitty_id, kitty)| Self::to_info(kitty_id, kitty))
+				.filter(|info| info.price.is_some())
+				.skip(offset as usize)
+				.take(limit.min(MAX_KITTIES_PER_PAGE) as usize)
+				.collect()
+		}
+
+		fn to_info(kitty_id: KittyIndex, kitty: Kitty<T>) -> KittyInfoOf<T> {
+			let (price, price_asset) = match (Self::dutch_listings(&kitty_id), kitty.price()) {
+				(Some(listing), _) => (Some(listing.price_at(<frame_system::Pallet<T>>::block_number())), None),
+				(None, Some((PriceCurrency::Native, price))) => (Some(price), None),
+				(None, Some((PriceCurrency::Asset(asset_id), price))) => (Some(price), Some(asset_id)),
+				(None, None) => (None, None),
+			};
+
+			KittyInfo {
+				id: kitty_id,
+				dna: kitty.dna(),
+				name: kitty.name(),
+				owner: kitty.owner(),
+				price,
+				price_asset,
+				gender: kitty.gender(),
+				traits: kitty.traits(),
+				parents: kitty.parents(),
+				generation: kitty.generation(),
+				life_stage: kitty.life_stage(T::KittyTime::now()),
+				created_date: kitty.created_date(),
+			}
+		}
+
+		/// an account below the existential deposit can only receive enough to be created
+		fn can_receive(who: &T::AccountId, amount: BalanceOf<T>, payment: Payment<T::AssetId>) -> bool {
+			match payment {
+				Payment::Asset(asset_id) => {
+					<T::Assets as fungibles::Inspect<T::AccountId>>::can_deposit(asset_id, who, amount, false) == DepositConsequence::Success
+				},
+				_ => !T::Currency::total_balance(who).is_zero() || amount >= T::Currency::minimum_balance(),
+			}
+		}
+
+		pub fn royalty_of(kitty_id: &KittyIndex) -> Perbill {
+			Self::royalty_override(kitty_id).unwrap_or_else(T::CreatorRoyalty::get)
+		}
+
+		pub(crate) fn ensure_not_locked(kitty_id: &KittyIndex) -> Result<(), Error<T>> {
+			ensure!(!<Auctions<T>>::contains_key(kitty_id), <Error<T>>::KittyInAuction);
+
+			ensure!(!<Leases<T>>::contains_key(kitty_id), <Error<T>>::KittyLeased);
+
+			ensure!(!<KittyBundle<T>>::contains_key(kitty_id), <Error<T>>::KittyInBundle);
+
+			ensure!(!<KittyVault<T>>::contains_key(kitty_id), <Error<T>>::KittyFractionalized);
+
+			ensure!(!<Stakes<T>>::contains_key(kitty_id), <Error<T>>::KittyStaked);
+
+			ensure!(!<KittyLoan<T>>::contains_key(kitty_id), <Error<T>>::KittyInLoan);
+
+			Ok(())
+		}
+
+		pub(crate) fn ensure_cooled_down(kitty_id: &KittyIndex) -> Result<(), Error<T>> {
+			if let Some(next_action_at) = Self::next_action_at(kitty_id) {
+				ensure!(T::KittyTime::now() >= next_action_at, <Error<T>>::KittyInCooldown);
+			}
+
+			Ok(())
+		}
+
+		/// the account allowed to use a leased kitty, `None` when it is not leased
+		pub fn user_of(kitty_id: &KittyIndex) -> Option<T::AccountId> {
+			Self::leases(kitty_id).map(|lease| lease.user)
+		}
+
+		fn ensure_adult(kitty: &Kitty<T>) -> Result<(), Error<T>> {
+			ensure!(kitty.life_stage(T::KittyTime::now()) != LifeStage::Kitten, <Error<T>>::KittyTooYoung);
+
+			Ok(())
+		}
+
+		/// hand the kitty to the highest bidder, or refund the bid if the sale can not go through
+		fn settle_auction(kitty_id: KittyIndex) {
+
+			let auction = match <Auctions<T>>::take(kitty_id) {
+				Some(auction) => auction,
+				None => return,
+			};
+
+			let (winner, amount) = match auction.highest_bid {
+				Some(highest_bid) => highest_bid,
+				None => {
+					Self::deposit_event(Event::AuctionEnded(kitty_id));
+					return;
+				},
+			};
+
+			let result = with_transaction(|| {
+				let result = Self::sell(kitty_id, &auction.seller, &winner, amount, Payment::Reserved);
+
+				match result {
+					Ok(()) => TransactionOutcome::Commit(result),
+					Err(_) => TransactionOutcome::Rollback(result),
+				}
+			});
+
+			match result {
+				Ok(()) => {
+					Self::deposit_event(Event::AuctionSettled(kitty_id, winner, amount));
+				},
+				Err(e) => {
+					log::warn!("Settle auction for Kitty {:?} failed: {:?}", kitty_id, e);
+
+					T::Currency::unreserve(&winner, amount);
+
+					Self::deposit_event(Event::AuctionBidRefunded(kitty_id, winner, amount));
+					Self::deposit_event(Event::AuctionEnded(kitty_id));
+				},
+			}
+		}
+
+		/// remove the kitty and everything attached to it, standing offers are refunded
+		pub(crate) fn do_burn(kitty_id: KittyIndex) -> DispatchResult {
+
+			let kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			Self::ensure_not_locked(&kitty_id)?;
+
+			let owner = kitty.owner();
+
+			<KittyOwner<T>>::mutate(&owner, |kitty_vec| {
+				kitty_vec.retain(|x| *x != kitty_id);
+			});
+
+			<Kitties<T>>::remove(&kitty_id);
+			<KittyCounter<T>>::mutate(|count| *count = count.saturating_sub(1));
+
+			if let Some(kitty_hash) = <KittyHash<T>>::take(&kitty_id) {
+				<KittyIdByHash<T>>::remove(kitty_hash);
+			}
+
+			// children keep their parents, only the lists pointing at this kitty are cleared
+			<Children<T>>::remove(&kitty_id);
+			if let Some((parent_a, parent_b)) = kitty.parents() {
+				for parent in [parent_a, parent_b] {
+					<Children<T>>::mutate(parent, |kitty_vec| kitty_vec.retain(|x| *x != kitty_id));
+				}
+			}
+
+			<DutchListings<T>>::remove(&kitty_id);
+			<KittyApprovals<T>>::remove(&kitty_id);
+			<RoyaltyOverride<T>>::remove(&kitty_id);
+			<NextActionAt<T>>::remove(&kitty_id);
+			<SireOffers<T>>::remove(&kitty_id);
+			<LeaseOffers<T>>::remove(&kitty_id);
+			Self::invalidate_swaps(&kitty_id);
+			Self::release_metadata(&kitty_id);
+
+			let bidders: Vec<T::AccountId> = <Offers<T>>::iter_key_prefix(&kitty_id).collect();
+			for bidder in bidders {
+				if let Some(offer) = Self::take_offer(&kitty_id, &bidder) {
+					T::Currency::unreserve(&bidder, offer.amount);
+				}
+			}
+			<KittyOfferCount<T>>::remove(&kitty_id);
+
+			Self::deposit_event(Event::KittyBurned(owner, kitty_id));
+
+			Ok(())
+		}
+
+		/// end whatever locks the kitty, see `force_burn`
+		fn unlock(kitty_id: KittyIndex) -> DispatchResult {
+			if let Some(auction) = <Auctions<T>>::take(kitty_id) {
+				<AuctionsEnding<T>>::mutate(auction.end, |kitty_vec| kitty_vec.retain(|id| *id != kitty_id));
+
+				if let Some((bidder, amount)) = auction.highest_bid {
+					T::Currency::unreserve(&bidder, amount);
+
+					Self::deposit_event(Event::AuctionBidRefunded(kitty_id, bidder, amount));
+				}
+
+				Self::deposit_event(Event::AuctionEnded(kitty_id));
+			}
+
+			if let Some(lease) = <Leases<T>>::take(kitty_id) {
+				<LeasesExpiring<T>>::mutate(lease.expiry, |kitty_vec| kitty_vec.retain(|id| *id != kitty_id));
+
+				Self::deposit_event(Event::LeaseEnded(kitty_id, lease.user));
+			}
+
+			if let Some(bundle_id) = Self::kitty_bundle(kitty_id) {
+				Self::remove_bundle(bundle_id);
+
+				Self::deposit_event(Event::BundleCancelled(bundle_id));
+			}
+
+			if let Some(asset_id) = <KittyVault<T>>::take(kitty_id) {
+				<Vaults<T>>::remove(asset_id);
+			}
+
+			if let Some(mut stake) = Self::stakes(kitty_id) {
+				Self::update_reward_pool();
+
+				Self::pay_rewards(kitty_id, &mut stake)?;
+
+				<Stakes<T>>::remove(kitty_id);
+				<TotalStakeWeight<T>>::mutate(|total| *total = total.saturating_sub(stake.weight as u64));
+
+				Self::deposit_event(Event::KittyUnstaked(kitty_id, stake.staker));
+			}
+
+			if let Some(loan_id) = Self::kitty_loan(kitty_id) {
+				if let Some(loan) = Self::loans(loan_id) {
+					Self::remove_loan(loan_id, &loan);
+				}
+
+				Self::deposit_event(Event::LoanCancelled(loan_id));
+			}
+
+			Ok(())
+		}
+
+		/// refund the deposit for the name and metadata of a kitty
+		fn release_metadata(kitty_id: &KittyIndex) {
+			if let Some(metadata) = <Metadata<T>>::take(kitty_id) {
+				T::Currency::unreserve(&metadata.depositor, metadata.deposit);
+			}
+		}
+
+		/// remove an offer and its expiry without touching the reserved funds
+		fn take_offer(kitty_id: &KittyIndex, bidder: &T::AccountId) -> Option<Offer<BalanceOf<T>, T::BlockNumber>> {
+			let offer = <Offers<T>>::take(kitty_id, bidder)?;
+
+			// free the slot in the expiry block for other offers
+			<OffersExpiring<T>>::mutate(offer.expiry, |offer_vec| {
+				offer_vec.retain(|(id, who)| !(id == kitty_id && who == bidder))
+			});
+
+			<KittyOfferCount<T>>::mutate(kitty_id, |count| *count = count.saturating_sub(1));
+			<AccountOfferCount<T>>::mutate(bidder, |count| *count = count.saturating_sub(1));
+
+			Some(offer)
+		}
+
+		fn expire_offer(kitty_id: KittyIndex, bidder: T::AccountId, now: T::BlockNumber) {
+			// the offer may have been withdrawn and made again with another expiry
+			match Self::offers(&kitty_id, &bidder) {
+				Some(offer) if offer.expiry == now => (),
+				_ => return,
+			}
+
+			if let Some(offer) = Self::take_offer(&kitty_id, &bidder) {
+				T::Currency::unreserve(&bidder, offer.amount);
+
+				Self::deposit_event(Event::OfferExpired(kitty_id, bidder));
+			}
+		}
+
+		fn end_lease(kitty_id: KittyIndex, now: T::BlockNumber) {
+			match Self::leases(&kitty_id) {
+				Some(lease) if lease.expiry == now => {
+					<Leases<T>>::remove(&kitty_id);
+
+					Self::deposit_event(Event::LeaseEnded(kitty_id, lease.user));
+				},
+				_ => (),
+			}
+		}
+
+		/// add the rewards of the blocks since the last update to the reward per weight
+		fn update_reward_pool() {
+			let now = <frame_system::Pallet<T>>::block_number();
+			let last = Self::last_reward_block();
+
+			if now <= last {
+				return;
+			}
+
+			let total_weight = Self::total_stake_weight();
+
+			// nothing is minted while no kitty is staked
+			if total_weight > 0 {
+				let blocks: u128 = (now - last).saturated_into();
+				let rewards = Self::reward_rate().saturated_into::<u128>().saturating_mul(blocks);
+
+				let increase = rewards.saturating_mul(REWARD_PRECISION) / total_weight as u128;
+
+				<RewardPerWeight<T>>::mutate(|reward_per_weight| *reward_per_weight = reward_per_weight.saturating_add(increase));
+			}
+
+			<LastRewardBlock<T>>::put(now);
+		}
+
+		/// mint the rewards earned by a staked kitty since its last claim, the pool must be up to date
+		fn pay_rewards(kitty_id: KittyIndex, stake: &mut Stake<AccountOf<T>>) -> DispatchResult {
+			let earned = (stake.weight as u128).saturating_mul(Self::reward_per_weight());
+
+			let pending = earned.saturating_sub(stake.reward_debt) / REWARD_PRECISION;
+
+			// the remainder below one unit stays with the stake
+			stake.reward_debt = stake.reward_debt.saturating_add(pending.saturating_mul(REWARD_PRECISION));
+
+			let amount: BalanceOf<T> = pending.saturated_into();
+
+			if let (false, Some(asset_id)) = (amount.is_zero(), Self::reward_asset()) {
+				T::Tokens::mint(asset_id, &stake.staker, amount)?;
+
+				Self::deposit_event(Event::RewardsClaimed(kitty_id, stake.staker.clone(), amount));
+			}
+
+			Ok(())
+		}
+
+		/// administrator of the share and reward assets
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// holds a fractionalized kitty and its buyout price
+		pub fn vault_account(asset_id: T::AssetId) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(asset_id)
+		}
+
+		/// remove a loan and unlock its collateral
+		fn remove_loan(loan_id: LoanId, loan: &Loan<AccountOf<T>, BalanceOf<T>, T::BlockNumber>) {
+			<Loans<T>>::remove(loan_id);
+			<KittyLoan<T>>::remove(&loan.kitty_id);
+		}
+
+		/// remove a bundle and unlock its kitties
+		fn remove_bundle(bundle_id: BundleId) -> Option<Bundle<AccountOf<T>, BalanceOf<T>, BoundedVec<KittyIndex, T::MaxBundleSize>>> {
+			let bundle = <Bundles<T>>::take(bundle_id)?;
+
+			for kitty_id in bundle.kitties.iter() {
+				<KittyBundle<T>>::remove(kitty_id);
+			}
+
+			Some(bundle)
+		}
+
+		/// refund and remove every swap the kitty is part of
+		fn invalidate_swaps(kitty_id: &KittyIndex) {
+			for swap_id in <KittySwaps<T>>::take(kitty_id) {
+				if Self::remove_swap(swap_id, true).is_some() {
+					Self::deposit_event(Event::SwapInvalidated(swap_id));
+				}
+			}
+		}
+
+		/// remove a swap from both of its kitties and its expiry, `refund` unreserves the top up of the proposer
+		fn remove_swap(swap_id: SwapId, refund: bool) -> Option<Swap<AccountOf<T>, BalanceOf<T>, T::BlockNumber>> {
+			let swap = <Swaps<T>>::take(swap_id)?;
+
+			<SwapsExpiring<T>>::mutate(swap.expiry, |swap_vec| swap_vec.retain(|id| *id != swap_id));
+
+			for kitty_id in [swap.offered, swap.wanted] {
+				<KittySwaps<T>>::mutate(kitty_id, |swap_vec| swap_vec.retain(|id| *id != swap_id));
+			}
+
+			if let (true, Some(amount)) = (refund, swap.top_up) {
+				T::Currency::unreserve(&swap.proposer, amount);
+			}
+
+			Some(swap)
+		}
+
+		pub(crate) fn transfer_to(kitty_id: KittyIndex, to: &T::AccountId) -> Result<(), Error<T>> {
+
+			Self::detach_from_owner(kitty_id)?;
+
+			Self::attach_to_owner(kitty_id, to)
+		}
+
+		/// remove the kitty from the kitties of its owner, it must be attached to a new owner right after
+		fn detach_from_owner(kitty_id: KittyIndex) -> Result<(), Error<T>> {
+
+			let kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			let owner = kitty.owner();
+
+			// remove old kitty owner
+			<KittyOwner<T>>::try_mutate(&owner, |kitty_vec| {
+				if let Some(position) = kitty_vec.iter().position(|x| *x == kitty_id) {
+					kitty_vec.swap_remove(position);
+					return Ok(());
+				}
+				Err(())
+			}).map_err(|_| <Error<T>>::KittyNotExists)
+		}
+
+		fn attach_to_owner(kitty_id: KittyIndex, to: &T::AccountId) -> Result<(), Error<T>> {
+
+			let mut kitty = Self::kitties(&kitty_id).ok_or(<Error<T>>::KittyNotExists)?;
+
+			// update kitty owner
+
+			kitty.set_price(None);
+			kitty.set_owner(to.clone());
+
+			// the name and metadata were paid for by the previous owner
+			kitty.set_name(Default::default());
+			Self::release_metadata(&kitty_id);
+
+			<Kitties<T>>::insert(kitty_id, kitty);
+			<DutchListings<T>>::remove(&kitty_id);
+			<KittyApprovals<T>>::remove(&kitty_id);
+			<NextActionAt<T>>::insert(&kitty_id, T::KittyTime::now().saturating_add(T::ActionCooldown::get()));
+			<SireOffers<T>>::remove(&kitty_id);
+			<LeaseOffers<T>>::remove(&kitty_id);
+			Self::invalidate_swaps(&kitty_id);
+
+			// the new owner can not keep an offer on its own kitty
+			if let Some(offer) = Self::take_offer(&kitty_id, to) {
+				T::Currency::unreserve(to, offer.amount);
+			}
 
 			// add new kitty owner s
 			<KittyOwner<T>>::try_mutate(&to, |kitty_vec| {
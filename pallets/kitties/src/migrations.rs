@@ -0,0 +1,328 @@
+//! Storage migrations for the kitties pallet.
+//!
+//! Every change to the layout of a stored type bumps `STORAGE_VERSION` in `lib.rs` and ships
+//! a `vN` module with a `MigrateToVN` upgrade, which the runtime lists in its `Executive`.
+
+pub mod v1 {
+	//! Kitties keyed by `T::Hash` are moved to sequential `KittyIndex` keys.
+	//!
+	//! Kitties are indexed in order of creation and their old hash is kept in `KittyIdByHash` and `KittyHash`.
+	//! Stored kitties still have the genesis layout, without parents or a creator, so no parents
+	//! are recorded and the current owner is taken as the creator.
+
+	use crate::*;
+	use codec::{Decode, Encode};
+	use frame_support::{log, pallet_prelude::*, storage_alias, traits::OnRuntimeUpgrade};
+	use sp_std::vec::Vec;
+
+	/// the kitty layout before any migration
+	#[derive(Encode, Decode)]
+	pub struct OldKitty<T: Config> {
+		pub dna: [u8; 16],
+		pub owner: AccountOf<T>,
+		pub price: Option<BalanceOf<T>>,
+		pub gender: Gender,
+		pub created_date: TimeOf<T>,
+	}
+
+	/// the kitty layout written by this migration
+	#[derive(Encode, Decode)]
+	pub struct KittyV1<T: Config> {
+		pub index: KittyIndex,
+		pub dna: [u8; 16],
+		pub owner: AccountOf<T>,
+		pub price: Option<BalanceOf<T>>,
+		pub gender: Gender,
+		pub created_date: TimeOf<T>,
+		pub parents: Option<(KittyIndex, KittyIndex)>,
+		pub creator: AccountOf<T>,
+	}
+
+	#[storage_alias]
+	type NewKitties<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, KittyIndex, KittyV1<T>>;
+
+	#[storage_alias]
+	type Kitties<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, <T as frame_system::Config>::Hash, OldKitty<T>>;
+
+	#[storage_alias]
+	type KittyOwner<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::AccountId,
+		BoundedVec<<T as frame_system::Config>::Hash, <T as Config>::MaxOwnerKitty>,
+		ValueQuery,
+	>;
+
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() >= 1 {
+				log::info!("Kitties storage is already at version 1, skipping migration");
+				return T::DbWeight::get().reads(1);
+			}
+
+			let weight = migrate::<T>();
+
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			weight.saturating_add(T::DbWeight::get().reads_writes(1, 1))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			// old keys can not be decoded once the storage is migrated
+			let kitty_count = if StorageVersion::get::<Pallet<T>>() >= 1 {
+				crate::Kitties::<T>::iter_keys().count() as u32
+			} else {
+				Kitties::<T>::iter_keys().count() as u32
+			};
+
+			Ok(kitty_count.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let kitty_count = u32::decode(&mut &state[..]).map_err(|_| "invalid pre upgrade state")?;
+
+			ensure!(StorageVersion::get::<Pallet<T>>() >= 1, "storage version not updated");
+
+			// later migrations may have changed the kitty layout already, only keys are checked
+			ensure!(crate::Kitties::<T>::iter_keys().count() as u32 == kitty_count, "kitties lost in migration");
+			ensure!(Pallet::<T>::count() == kitty_count, "kitty counter not set");
+
+			let mut owned_count = 0;
+			for (_, kitty_vec) in crate::KittyOwner::<T>::iter() {
+				for kitty_index in kitty_vec {
+					ensure!(crate::Kitties::<T>::contains_key(kitty_index), "owned kitty missing");
+					ensure!(kitty_index < Pallet::<T>::next_kitty_index(), "next kitty index not set");
+					owned_count += 1;
+				}
+			}
+			ensure!(owned_count == kitty_count, "kitty missing from its owner");
+
+			Ok(())
+		}
+	}
+
+	pub fn migrate<T: Config>() -> Weight {
+		let mut reads: u64 = 0;
+		let mut writes: u64 = 0;
+
+		let mut old_kitties: Vec<_> = Kitties::<T>::drain().collect();
+		old_kitties.sort_by_key(|(_, kitty)| kitty.created_date);
+
+		let _ = KittyOwner::<T>::clear(u32::MAX, None);
+
+		for (kitty_index, (kitty_hash, old)) in old_kitties.iter().enumerate() {
+			let kitty_index = kitty_index as KittyIndex;
+
+			let _ = crate::KittyOwner::<T>::try_mutate(&old.owner, |kitty_vec| kitty_vec.try_push(kitty_index));
+
+			NewKitties::<T>::insert(kitty_index, KittyV1::<T> {
+				index: kitty_index,
+				dna: old.dna,
+				owner: old.owner.clone(),
+				price: old.price,
+				gender: old.gender.clone(),
+				created_date: old.created_date,
+				parents: None,
+				creator: old.owner.clone(),
+			});
+			crate::KittyIdByHash::<T>::insert(kitty_hash, kitty_index);
+			crate::KittyHash::<T>::insert(kitty_index, kitty_hash);
+
+			reads += 1;
+			writes += 5;
+		}
+
+		let next_kitty_index = old_kitties.len() as KittyIndex;
+		crate::NextKittyIndex::<T>::put(next_kitty_index);
+		crate::KittyCounter::<T>::put(next_kitty_index);
+
+		log::info!("Migrated {} kitties to sequential indexes", next_kitty_index);
+
+		T::DbWeight::get().reads_writes(reads, writes + 2)
+	}
+}
+
+pub mod v2 {
+	//! Kitties get an empty `name`.
+
+	use super::v1::KittyV1;
+	use crate::*;
+	use codec::{Decode, Encode};
+	use frame_support::{log, pallet_prelude::*, storage_alias, traits::OnRuntimeUpgrade};
+	#[cfg(feature = "try-runtime")]
+	use sp_std::vec::Vec;
+
+	/// the kitty layout written by this migration
+	#[derive(Encode, Decode)]
+	pub struct KittyV2<T: Config> {
+		pub index: KittyIndex,
+		pub dna: [u8; 16],
+		pub owner: AccountOf<T>,
+		pub price: Option<BalanceOf<T>>,
+		pub gender: Gender,
+		pub created_date: TimeOf<T>,
+		pub parents: Option<(KittyIndex, KittyIndex)>,
+		pub creator: AccountOf<T>,
+		pub name: BoundedVec<u8, <T as Config>::MaxNameLength>,
+	}
+
+	#[storage_alias]
+	type Kitties<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, KittyIndex, KittyV2<T>>;
+
+	pub struct MigrateToV2<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() >= 2 {
+				log::info!("Kitties storage is already at version 2, skipping migration");
+				return T::DbWeight::get().reads(1);
+			}
+
+			let mut translated: u64 = 0;
+
+			Kitties::<T>::translate::<KittyV1<T>, _>(|_, old| {
+				translated += 1;
+
+				Some(KittyV2 {
+					index: old.index,
+					dna: old.dna,
+					owner: old.owner,
+					price: old.price,
+					gender: old.gender,
+					created_date: old.created_date,
+					parents: old.parents,
+					creator: old.creator,
+					name: Default::default(),
+				})
+			});
+
+			StorageVersion::new(2).put::<Pallet<T>>();
+
+			log::info!("Added names to {} kitties", translated);
+
+			T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((crate::Kitties::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let kitty_count = u32::decode(&mut &state[..]).map_err(|_| "invalid pre upgrade state")?;
+
+			ensure!(StorageVersion::get::<Pallet<T>>() >= 2, "storage version not updated");
+
+			ensure!(crate::Kitties::<T>::iter_keys().count() as u32 == kitty_count, "kitties lost in migration");
+
+			// later migrations change the layout again
+			if StorageVersion::get::<Pallet<T>>() == 2 {
+				ensure!(Kitties::<T>::iter().count() as u32 == kitty_count, "kitty does not decode");
+			}
+
+			Ok(())
+		}
+	}
+}
+
+pub mod v3 {
+	//! Kitties get a `generation` and bred kitties are indexed in `Children`.
+	//!
+	//! Parents always have a lower index than their children, so generations are computed
+	//! in index order.
+
+	use super::v2::KittyV2;
+	use crate::*;
+	#[cfg(feature = "try-runtime")]
+	use codec::{Decode, Encode};
+	use frame_support::{log, pallet_prelude::*, storage_alias, traits::OnRuntimeUpgrade};
+	use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
+
+	#[storage_alias]
+	type OldKitties<T: Config> = StorageMap<Pallet<T>, Blake2_128Concat, KittyIndex, KittyV2<T>>;
+
+	pub struct MigrateToV3<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() >= 3 {
+				log::info!("Kitties storage is already at version 3, skipping migration");
+				return T::DbWeight::get().reads(1);
+			}
+
+			let mut old_kitties: Vec<_> = OldKitties::<T>::iter().collect();
+			old_kitties.sort_by_key(|(kitty_index, _)| *kitty_index);
+
+			let mut generation_of: BTreeMap<KittyIndex, u16> = BTreeMap::new();
+			let mut writes: u64 = 0;
+
+			for (kitty_index, old) in old_kitties.iter() {
+				let mut kitty = Kitty::<T>::new(old.creator.clone(), old.dna, old.gender.clone(), old.created_date);
+				kitty.set_index(old.index);
+				kitty.set_owner(old.owner.clone());
+				kitty.set_price(old.price.map(|price| (PriceCurrency::Native, price)));
+				kitty.set_name(old.name.clone());
+
+				if let Some((parent_a, parent_b)) = old.parents {
+					kitty.set_parents((parent_a, parent_b));
+
+					// burned parents count as generation 0
+					let generation_a = generation_of.get(&parent_a).copied().unwrap_or_default();
+					let generation_b = generation_of.get(&parent_b).copied().unwrap_or_default();
+					kitty.set_generation(generation_a.max(generation_b).saturating_add(1));
+
+					for parent in [parent_a, parent_b] {
+						let _ = crate::Children::<T>::try_mutate(parent, |kitty_vec| kitty_vec.try_push(*kitty_index));
+					}
+					writes += 2;
+				}
+
+				generation_of.insert(*kitty_index, kitty.generation());
+
+				crate::Kitties::<T>::insert(kitty_index, kitty);
+				writes += 1;
+			}
+
+			StorageVersion::new(3).put::<Pallet<T>>();
+
+			log::info!("Added generations to {} kitties", old_kitties.len());
+
+			T::DbWeight::get().reads_writes(old_kitties.len() as u64 + 1, writes + 1)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok((crate::Kitties::<T>::iter_keys().count() as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let kitty_count = u32::decode(&mut &state[..]).map_err(|_| "invalid pre upgrade state")?;
+
+			ensure!(StorageVersion::get::<Pallet<T>>() >= 3, "storage version not updated");
+
+			ensure!(crate::Kitties::<T>::iter_keys().count() as u32 == kitty_count, "kitties lost in migration");
+
+			// later migrations change the layout again
+			if StorageVersion::get::<Pallet<T>>() == 3 {
+				for (_, kitty) in crate::Kitties::<T>::iter() {
+					if let Some((parent_a, parent_b)) = kitty.parents() {
+						ensure!(kitty.generation() > 0, "bred kitty without generation");
+						ensure!(
+							crate::Children::<T>::get(parent_a).contains(&kitty.index())
+								&& crate::Children::<T>::get(parent_b).contains(&kitty.index()),
+							"kitty missing from the children of its parents"
+						);
+					}
+				}
+			}
+
+			Ok(())
+		}
+	}
+}
@@ -1,10 +1,12 @@
 use crate as pallet_kitty;
-use frame_support::traits::{ConstU32, ConstU64, Everything};
+use frame_support::{parameter_types, traits::{ConstU32, ConstU64, Everything, Hooks}, PalletId};
 use sp_core::H256;
 use sp_runtime::testing::Header;
 use sp_runtime::traits::{BlakeTwo256, IdentityLookup};
+use sp_runtime::Perbill;
 
 use frame_system as system;
+use frame_system::EnsureRoot;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -19,6 +21,8 @@ frame_support::construct_runtime!(
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip,
 		Balance: pallet_balances,
 		Timestamp: pallet_timestamp, 
+		Assets: pallet_assets,
+		Token: pallet_token,
         PalletKitty: pallet_kitty,
 	}
 );
@@ -71,12 +75,80 @@ impl pallet_timestamp::Config for Test {
 	type WeightInfo = ();
 }
 
+impl pallet_assets::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Balance = u64;
+	type AssetId = u32;
+	type Currency = Balance;
+	type ForceOrigin = EnsureRoot<u64>;
+	type AssetDeposit = ConstU64<1>;
+	type AssetAccountDeposit = ConstU64<1>;
+	type MetadataDepositBase = ConstU64<1>;
+	type MetadataDepositPerByte = ConstU64<1>;
+	type ApprovalDeposit = ConstU64<1>;
+	type StringLimit = ConstU32<50>;
+	type Freezer = ();
+	type Extra = ();
+	type WeightInfo = ();
+}
+
+impl pallet_token::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type AssetHandler = Assets;
+	type AssetId = u32;
+}
+
+parameter_types! {
+	pub const KittiesPalletId: PalletId = PalletId(*b"py/kitty");
+	pub const CreatorRoyalty: Perbill = Perbill::from_percent(10);
+	pub const MarketplaceFee: Perbill = Perbill::from_percent(10);
+	pub const FeeDestination: u64 = FEE_ACCOUNT;
+}
+
+pub const FEE_ACCOUNT: u64 = 99;
+
+// asset created at genesis with a minimum balance of 1
+pub const ASSET_ID: u32 = 7;
+
+pub const ADULT_AGE: u64 = 1_000;
+pub const ELDER_AGE: u64 = 100_000;
+pub const ACTION_COOLDOWN: u64 = 500;
+
+// large enough for the reward rate bound to be reached with u64 balances
+pub const MAX_BLOCKS_BETWEEN_UPDATES: u64 = 1 << 40;
+
 impl pallet_kitty::Config for Test {
     type RuntimeEvent = RuntimeEvent;
 	type KittyRandomness = RandomnessCollectiveFlip;
 	type Currency = Balance;
 	type MaxOwnerKitty = ConstU32<3>;
+	type MaxAuctionDuration = ConstU64<100>;
+	type MaxDutchDuration = ConstU64<100>;
+	type MaxAuctionsPerBlock = ConstU32<2>;
+	type MaxOffers = ConstU32<2>;
+	type MaxOffersPerBlock = ConstU32<4>;
+	type CreatorRoyalty = CreatorRoyalty;
+	type MarketplaceFee = MarketplaceFee;
+	type FeeDestination = FeeDestination;
 	type KittyTime = Timestamp;
+	type MaxNameLength = ConstU32<8>;
+	type MaxMetadataLength = ConstU32<16>;
+	type MetadataDepositPerByte = ConstU64<1>;
+	type MaxChildren = ConstU32<2>;
+	type AdultAge = ConstU64<ADULT_AGE>;
+	type ElderAge = ConstU64<ELDER_AGE>;
+	type ActionCooldown = ConstU64<ACTION_COOLDOWN>;
+	type MaxLeaseDuration = ConstU64<100>;
+	type MaxLeasesPerBlock = ConstU32<2>;
+	type MaxSwaps = ConstU32<2>;
+	type MaxSwapsPerBlock = ConstU32<4>;
+	type MaxBundleSize = ConstU32<3>;
+	type AssetId = u32;
+	type Assets = Assets;
+	type AssetAdminOrigin = EnsureRoot<u64>;
+	type PalletId = KittiesPalletId;
+	type Tokens = Token;
+	type MaxBlocksBetweenUpdates = ConstU64<MAX_BLOCKS_BETWEEN_UPDATES>;
 	type WeightInfo = pallet_kitty::weights::SubstrateWeight<Test>;
 }
 
@@ -87,5 +159,28 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	}
 	.assimilate_storage(&mut t)
 	.unwrap();
+	pallet_assets::GenesisConfig::<Test> {
+		assets: vec![(ASSET_ID, 1, true, 1)],
+		metadata: vec![],
+		accounts: vec![(ASSET_ID, 4, 100), (ASSET_ID, 5, 100)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	pallet_kitty::GenesisConfig::<Test> {
+		kitty_owner: vec![(3, [0; 16]), (3, [1; 16]), (4, [2; 16])],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
 	t.into()
 }
+
+pub fn pass_time(ms: u64) {
+	Timestamp::set_timestamp(Timestamp::now() + ms);
+}
+
+pub fn run_to_block(n: u64) {
+	while System::block_number() < n {
+		System::set_block_number(System::block_number() + 1);
+		PalletKitty::on_initialize(System::block_number());
+	}
+}
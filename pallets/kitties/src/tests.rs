@@ -1,6 +1,7 @@
-use crate::mock::*;
+use crate::{mock::*, DutchListing, Error, Event, Gender, LifeStage, PriceCurrency, REWARD_PRECISION};
 
-use frame_support::{assert_ok};
+use frame_support::{assert_noop, assert_ok, traits::Hooks};
+use sp_runtime::Perbill;
 
 #[test]
 fn create_new_kitty_should_work() {
@@ -27,4 +28,1406 @@ fn transfer_kitty_should_work() {
         assert_ok!(PalletKitty::transfer(owner_origin, to, kitty_id));
 
     })
-}
\ No newline at end of file
+}
+
+#[test]
+fn buy_kitty_should_pay_seller() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let seller = 1;
+        let buyer = 2;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(seller)));
+
+        let kitty_id = PalletKitty::kitty_owner(seller).get(0).unwrap().clone();
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Native, 40))));
+        assert_ok!(PalletKitty::buy(RuntimeOrigin::signed(buyer), kitty_id));
+
+        assert_eq!(Balance::free_balance(seller), 136);
+        assert_eq!(Balance::free_balance(buyer), 60);
+        assert_eq!(Balance::free_balance(FEE_ACCOUNT), 4);
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), buyer);
+        System::assert_last_event(Event::KittySold(kitty_id, seller, buyer, 40, 0).into());
+    })
+}
+
+#[test]
+fn buy_kitty_should_not_reap_buyer() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let seller = 1;
+        let buyer = 2;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(seller)));
+
+        let kitty_id = PalletKitty::kitty_owner(seller).get(0).unwrap().clone();
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Native, 100))));
+        assert_noop!(PalletKitty::buy(RuntimeOrigin::signed(buyer), kitty_id), Error::<Test>::BuyerWouldBeReaped);
+
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Native, 101))));
+        assert_noop!(PalletKitty::buy(RuntimeOrigin::signed(buyer), kitty_id), Error::<Test>::NotEnoughMoney);
+    })
+}
+
+#[test]
+fn breed_kitty_should_work() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+        let kitties = PalletKitty::kitty_owner(owner);
+        let (male, female) = (kitties[0], kitties[1]);
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, female));
+
+        let child_id = PalletKitty::kitty_owner(owner)[2];
+        let child = PalletKitty::kitties(child_id).unwrap();
+        assert_eq!(child.parents(), Some((male, female)));
+        assert_eq!(child.owner(), owner);
+        assert_eq!(PalletKitty::count(), 4);
+        System::assert_last_event(Event::KittyBred(owner, child_id, male, female).into());
+    })
+}
+
+#[test]
+fn breed_kitty_should_need_free_adult_rested_parents() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+        let (male, female) = (0, 1);
+
+        assert_noop!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, female), Error::<Test>::KittyTooYoung);
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::create_auction(RuntimeOrigin::signed(owner), male, 10, 5));
+        assert_noop!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, female), Error::<Test>::KittyInAuction);
+
+        run_to_block(6);
+        assert_ok!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, female));
+        assert_eq!(PalletKitty::next_action_at(male), Some(ADULT_AGE + ACTION_COOLDOWN));
+        assert_eq!(PalletKitty::next_action_at(female), Some(ADULT_AGE + ACTION_COOLDOWN));
+        assert_noop!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, female), Error::<Test>::KittyInCooldown);
+    })
+}
+
+#[test]
+fn child_dna_should_copy_whole_bytes_from_the_parents() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (dna_a, dna_b) = ([0x0f; 16], [0xf0; 16]);
+
+        let dna = PalletKitty::mix_dna(&dna_a, &dna_b);
+        assert!(dna.iter().all(|gene| *gene == 0x0f || *gene == 0xf0));
+    })
+}
+
+#[test]
+fn breed_kitty_should_check_parents() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+        let kitties = PalletKitty::kitty_owner(owner);
+        let (male, female) = (kitties[0], kitties[1]);
+
+        assert_noop!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, male), Error::<Test>::SameParent);
+        assert_noop!(PalletKitty::breed(RuntimeOrigin::signed(1), male, female), Error::<Test>::NotKittyOwner);
+
+        let other_male = PalletKitty::kitty_owner(4)[0];
+        assert_eq!(PalletKitty::kitties(other_male).unwrap().gender(), Gender::MALE);
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(4), owner, other_male));
+        assert_noop!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, other_male), Error::<Test>::SameGender);
+    })
+}
+
+#[test]
+fn auction_should_settle_to_highest_bidder() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let seller = 1;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(seller)));
+
+        let kitty_id = PalletKitty::kitty_owner(seller)[0];
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::create_auction(RuntimeOrigin::signed(seller), kitty_id, 10, 5));
+        assert_noop!(PalletKitty::transfer(RuntimeOrigin::signed(seller), 2, kitty_id), Error::<Test>::KittyInAuction);
+        assert_noop!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Native, 10))), Error::<Test>::KittyInAuction);
+
+        assert_noop!(PalletKitty::bid(RuntimeOrigin::signed(2), kitty_id, 5), Error::<Test>::BidBelowReserve);
+        assert_ok!(PalletKitty::bid(RuntimeOrigin::signed(2), kitty_id, 20));
+        assert_eq!(Balance::reserved_balance(2), 20);
+
+        assert_noop!(PalletKitty::bid(RuntimeOrigin::signed(4), kitty_id, 20), Error::<Test>::BidTooLow);
+        assert_ok!(PalletKitty::bid(RuntimeOrigin::signed(4), kitty_id, 30));
+        assert_eq!(Balance::reserved_balance(2), 0);
+        assert_eq!(Balance::reserved_balance(4), 30);
+
+        run_to_block(6);
+
+        assert!(PalletKitty::auctions(kitty_id).is_none());
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), 4);
+        assert_eq!(Balance::free_balance(4), 70);
+        assert_eq!(Balance::reserved_balance(4), 0);
+        assert_eq!(Balance::free_balance(seller), 127);
+        System::assert_last_event(Event::AuctionSettled(kitty_id, 4, 30).into());
+    })
+}
+
+#[test]
+fn auction_without_bids_should_end() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let seller = 1;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(seller)));
+
+        let kitty_id = PalletKitty::kitty_owner(seller)[0];
+
+        pass_time(ADULT_AGE);
+        assert_noop!(PalletKitty::create_auction(RuntimeOrigin::signed(seller), kitty_id, 10, 0), Error::<Test>::InvalidAuctionDuration);
+        assert_ok!(PalletKitty::create_auction(RuntimeOrigin::signed(seller), kitty_id, 10, 5));
+
+        run_to_block(6);
+
+        assert_noop!(PalletKitty::bid(RuntimeOrigin::signed(2), kitty_id, 20), Error::<Test>::AuctionNotExists);
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), seller);
+        System::assert_last_event(Event::AuctionEnded(kitty_id).into());
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(seller), 2, kitty_id));
+    })
+}
+
+#[test]
+fn dutch_price_should_decay_linearly() {
+    let listing = DutchListing { start_price: 90u64, floor_price: 10u64, start: 10u64, duration: 8u64 };
+
+    assert_eq!(listing.price_at(0), 90);
+    assert_eq!(listing.price_at(10), 90);
+    assert_eq!(listing.price_at(11), 80);
+    assert_eq!(listing.price_at(14), 50);
+    assert_eq!(listing.price_at(17), 20);
+    assert_eq!(listing.price_at(18), 10);
+    assert_eq!(listing.price_at(1_000), 10);
+
+    let flat = DutchListing { start_price: 10u64, floor_price: 10u64, start: 0u64, duration: 1u64 };
+    assert_eq!(flat.price_at(0), 10);
+
+    // durations beyond u32 still decay over the whole duration
+    let long = DutchListing { start_price: 100u64, floor_price: 0u64, start: 0u64, duration: 1u64 << 40 };
+    assert_eq!(long.price_at(1u64 << 39), 50);
+    assert_eq!(long.price_at(u32::MAX as u64 + 1), 100);
+}
+
+#[test]
+fn buy_dutch_listing_should_pay_current_price() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let seller = 1;
+        let buyer = 2;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(seller)));
+
+        let kitty_id = PalletKitty::kitty_owner(seller)[0];
+
+        pass_time(ADULT_AGE);
+        assert_noop!(PalletKitty::list_dutch(RuntimeOrigin::signed(seller), kitty_id, 10, 90, 8), Error::<Test>::InvalidDutchListing);
+        assert_noop!(PalletKitty::list_dutch(RuntimeOrigin::signed(seller), kitty_id, 90, 10, 101), Error::<Test>::InvalidDutchListing);
+        assert_ok!(PalletKitty::list_dutch(RuntimeOrigin::signed(seller), kitty_id, 90, 10, 8));
+        assert_noop!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Native, 50))), Error::<Test>::KittyInDutchListing);
+
+        run_to_block(5);
+
+        assert_ok!(PalletKitty::buy(RuntimeOrigin::signed(buyer), kitty_id));
+        assert_eq!(Balance::free_balance(seller), 145);
+        assert_eq!(Balance::free_balance(buyer), 50);
+        assert!(PalletKitty::dutch_listings(kitty_id).is_none());
+        System::assert_last_event(Event::KittySold(kitty_id, seller, buyer, 50, 0).into());
+    })
+}
+
+#[test]
+fn cancel_dutch_listing_should_work() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let seller = 1;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(seller)));
+
+        let kitty_id = PalletKitty::kitty_owner(seller)[0];
+
+        assert_noop!(PalletKitty::cancel_dutch_listing(RuntimeOrigin::signed(seller), kitty_id), Error::<Test>::DutchListingNotExists);
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::list_dutch(RuntimeOrigin::signed(seller), kitty_id, 90, 10, 8));
+        assert_ok!(PalletKitty::cancel_dutch_listing(RuntimeOrigin::signed(seller), kitty_id));
+
+        System::assert_last_event(Event::DutchListingCancelled(kitty_id).into());
+        assert_noop!(PalletKitty::buy(RuntimeOrigin::signed(2), kitty_id), Error::<Test>::KittyPriceNone);
+    })
+}
+
+#[test]
+fn accept_offer_should_work() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 1;
+        let bidder = 2;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(owner)));
+
+        let kitty_id = PalletKitty::kitty_owner(owner)[0];
+
+        assert_noop!(PalletKitty::make_offer(RuntimeOrigin::signed(owner), kitty_id, 30, 10), Error::<Test>::OfferOnOwnKitty);
+        assert_noop!(PalletKitty::make_offer(RuntimeOrigin::signed(bidder), kitty_id, 30, 1), Error::<Test>::OfferExpired);
+        assert_noop!(PalletKitty::make_offer(RuntimeOrigin::signed(bidder), kitty_id, 0, 10), Error::<Test>::OfferTooLow);
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(bidder), kitty_id, 30, 10));
+        assert_noop!(PalletKitty::make_offer(RuntimeOrigin::signed(bidder), kitty_id, 40, 10), Error::<Test>::OfferExists);
+        assert_eq!(Balance::reserved_balance(bidder), 30);
+
+        assert_noop!(PalletKitty::accept_offer(RuntimeOrigin::signed(bidder), kitty_id, bidder), Error::<Test>::NotKittyOwner);
+        assert_ok!(PalletKitty::accept_offer(RuntimeOrigin::signed(owner), kitty_id, bidder));
+
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), bidder);
+        assert_eq!(Balance::reserved_balance(bidder), 0);
+        assert_eq!(Balance::free_balance(bidder), 70);
+        assert_eq!(Balance::free_balance(owner), 127);
+        assert_eq!(PalletKitty::kitty_offer_count(kitty_id), 0);
+        assert_eq!(PalletKitty::account_offer_count(bidder), 0);
+        System::assert_last_event(Event::OfferAccepted(kitty_id, bidder, 30).into());
+    })
+}
+
+#[test]
+fn offers_should_be_bounded() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 1;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(owner)));
+
+        let kitty_id = PalletKitty::kitty_owner(owner)[0];
+
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(2), kitty_id, 10, 10));
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(4), kitty_id, 10, 10));
+        assert_noop!(PalletKitty::make_offer(RuntimeOrigin::signed(5), kitty_id, 10, 10), Error::<Test>::TooManyOffers);
+
+        assert_ok!(PalletKitty::withdraw_offer(RuntimeOrigin::signed(2), kitty_id));
+        assert_eq!(Balance::reserved_balance(2), 0);
+        assert_noop!(PalletKitty::withdraw_offer(RuntimeOrigin::signed(2), kitty_id), Error::<Test>::OfferNotExists);
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(5), kitty_id, 10, 10));
+    })
+}
+
+#[test]
+fn closed_offers_should_free_their_expiry_slot() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        // fill the four slots of block 10
+        for bidder in [2, 5] {
+            for kitty_id in [0, 1] {
+                assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(bidder), kitty_id, 10, 10));
+            }
+        }
+        assert_noop!(PalletKitty::make_offer(RuntimeOrigin::signed(1), 2, 10, 10), Error::<Test>::TooManyOffersExpiring);
+
+        assert_ok!(PalletKitty::withdraw_offer(RuntimeOrigin::signed(2), 0));
+        assert_eq!(PalletKitty::offers_expiring(10).len(), 3);
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(1), 2, 10, 10));
+
+        assert_ok!(PalletKitty::accept_offer(RuntimeOrigin::signed(3), 1, 5));
+        assert_eq!(PalletKitty::offers_expiring(10).len(), 3);
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(4), 0, 10, 10));
+    })
+}
+
+#[test]
+fn offer_should_expire() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 1;
+        let bidder = 2;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(owner)));
+
+        let kitty_id = PalletKitty::kitty_owner(owner)[0];
+
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(bidder), kitty_id, 30, 5));
+
+        run_to_block(5);
+
+        assert!(PalletKitty::offers(kitty_id, bidder).is_none());
+        assert_eq!(Balance::reserved_balance(bidder), 0);
+        assert_eq!(PalletKitty::account_offer_count(bidder), 0);
+        System::assert_last_event(Event::OfferExpired(kitty_id, bidder).into());
+        assert_noop!(PalletKitty::accept_offer(RuntimeOrigin::signed(owner), kitty_id, bidder), Error::<Test>::OfferNotExists);
+    })
+}
+
+#[test]
+fn sale_should_pay_creator_royalty() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let creator = 1;
+        let seller = 2;
+        let buyer = 4;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(creator)));
+
+        let kitty_id = PalletKitty::kitty_owner(creator)[0];
+
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(creator), seller, kitty_id));
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Native, 50))));
+        assert_ok!(PalletKitty::buy(RuntimeOrigin::signed(buyer), kitty_id));
+
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().creator(), creator);
+        assert_eq!(Balance::free_balance(creator), 105);
+        assert_eq!(Balance::free_balance(seller), 140);
+        assert_eq!(Balance::free_balance(buyer), 50);
+        System::assert_last_event(Event::KittySold(kitty_id, seller, buyer, 50, 5).into());
+    })
+}
+
+#[test]
+fn creator_can_only_lower_royalty() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let creator = 1;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(creator)));
+
+        let kitty_id = PalletKitty::kitty_owner(creator)[0];
+
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(creator), 2, kitty_id));
+        assert_noop!(PalletKitty::set_royalty(RuntimeOrigin::signed(2), kitty_id, Perbill::from_percent(5)), Error::<Test>::NotKittyCreator);
+        assert_noop!(PalletKitty::set_royalty(RuntimeOrigin::signed(creator), kitty_id, Perbill::from_percent(20)), Error::<Test>::RoyaltyTooHigh);
+
+        assert_ok!(PalletKitty::set_royalty(RuntimeOrigin::signed(creator), kitty_id, Perbill::from_percent(2)));
+        assert_eq!(PalletKitty::royalty_of(&kitty_id), Perbill::from_percent(2));
+        assert_noop!(PalletKitty::set_royalty(RuntimeOrigin::signed(creator), kitty_id, Perbill::from_percent(5)), Error::<Test>::RoyaltyTooHigh);
+    })
+}
+
+#[test]
+fn sale_should_pay_marketplace_fee() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let creator = 1;
+        let seller = 2;
+        let buyer = 4;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(creator)));
+
+        let kitty_id = PalletKitty::kitty_owner(creator)[0];
+
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(creator), seller, kitty_id));
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Native, 20))));
+        assert_ok!(PalletKitty::buy(RuntimeOrigin::signed(buyer), kitty_id));
+
+        // the royalty and the fee both come out of the seller's proceeds
+        assert_eq!(Balance::free_balance(FEE_ACCOUNT), 2);
+        assert_eq!(Balance::free_balance(creator), 102);
+        assert_eq!(Balance::free_balance(seller), 116);
+        System::assert_has_event(Event::MarketplaceFeePaid(kitty_id, FEE_ACCOUNT, 2).into());
+    })
+}
+
+#[test]
+fn listed_kitties_should_page_kitties_for_sale() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 1;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(owner)));
+        System::set_block_number(2);
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(owner)));
+
+        let kitties = PalletKitty::kitty_owner(owner);
+
+        assert_eq!(PalletKitty::kitties_of(&owner).len(), 2);
+        assert!(PalletKitty::listed_kitties(0, 10).is_empty());
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(owner), kitties[0], Some((PriceCurrency::Native, 40))));
+        assert_ok!(PalletKitty::list_dutch(RuntimeOrigin::signed(owner), kitties[1], 90, 10, 8));
+
+        assert_eq!(PalletKitty::listed_kitties(0, 10).len(), 2);
+        assert_eq!(PalletKitty::listed_kitties(1, 10).len(), 1);
+        assert_eq!(PalletKitty::listed_kitties(0, 1).len(), 1);
+        assert_eq!(PalletKitty::kitty_info(&kitties[0]).unwrap().price, Some(40));
+        assert_eq!(PalletKitty::kitty_info(&kitties[1]).unwrap().price, Some(90));
+    })
+}
+
+#[test]
+fn transfer_from_should_respect_approvals() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 1;
+        let operator = 2;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(owner)));
+
+        let kitty_id = PalletKitty::kitty_owner(owner)[0];
+
+        assert_noop!(PalletKitty::transfer_from(RuntimeOrigin::signed(operator), owner, 4, kitty_id), Error::<Test>::NotApproved);
+        assert_noop!(PalletKitty::approve(RuntimeOrigin::signed(owner), kitty_id, owner), Error::<Test>::ApproveToSelf);
+
+        assert_ok!(PalletKitty::approve(RuntimeOrigin::signed(owner), kitty_id, operator));
+        assert_noop!(PalletKitty::transfer_from(RuntimeOrigin::signed(operator), 4, 5, kitty_id), Error::<Test>::NotKittyOwner);
+        assert_ok!(PalletKitty::transfer_from(RuntimeOrigin::signed(operator), owner, 4, kitty_id));
+
+        // the approval does not survive the ownership change
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), 4);
+        assert!(PalletKitty::kitty_approval(kitty_id).is_none());
+        assert_noop!(PalletKitty::transfer_from(RuntimeOrigin::signed(operator), 4, owner, kitty_id), Error::<Test>::NotApproved);
+        System::assert_last_event(Event::KittyTranfered(kitty_id, 4).into());
+    })
+}
+
+#[test]
+fn operator_should_move_every_kitty() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+        let operator = 2;
+        let kitties = PalletKitty::kitty_owner(owner);
+
+        assert_ok!(PalletKitty::set_approval_for_all(RuntimeOrigin::signed(owner), operator, true));
+        assert!(PalletKitty::is_approved_for_all(owner, operator));
+
+        assert_ok!(PalletKitty::transfer_from(RuntimeOrigin::signed(operator), owner, 4, kitties[0]));
+        assert_ok!(PalletKitty::approve(RuntimeOrigin::signed(operator), kitties[1], 5));
+
+        assert_ok!(PalletKitty::set_approval_for_all(RuntimeOrigin::signed(owner), operator, false));
+        assert_noop!(PalletKitty::transfer_from(RuntimeOrigin::signed(operator), owner, 4, kitties[1]), Error::<Test>::NotApproved);
+        assert_ok!(PalletKitty::transfer_from(RuntimeOrigin::signed(5), owner, 4, kitties[1]));
+    })
+}
+
+#[test]
+fn nonfungibles_traits_should_work() {
+    use frame_support::traits::tokens::nonfungibles::{Inspect, InspectEnumerable, Mutate, Transfer};
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let item = 7;
+
+        assert_noop!(<PalletKitty as Mutate<u64>>::mint_into(&(), &1, &1), Error::<Test>::KittyExists);
+        assert_ok!(<PalletKitty as Mutate<u64>>::mint_into(&(), &item, &1));
+        assert_eq!(PalletKitty::next_kitty_index(), 8);
+        assert_noop!(<PalletKitty as Mutate<u64>>::mint_into(&(), &item, &2), Error::<Test>::KittyExists);
+        assert_eq!(<PalletKitty as Inspect<u64>>::owner(&(), &item), Some(1));
+        assert_eq!(
+            <PalletKitty as Inspect<u64>>::attribute(&(), &item, b"dna"),
+            Some(PalletKitty::kitties(item).unwrap().dna().to_vec())
+        );
+        assert_eq!(<PalletKitty as InspectEnumerable<u64>>::owned(&1).collect::<Vec<_>>(), vec![((), item)]);
+        assert_eq!(<PalletKitty as InspectEnumerable<u64>>::items(&()).count(), 4);
+
+        assert_ok!(<PalletKitty as Transfer<u64>>::transfer(&(), &item, &2));
+        assert_eq!(<PalletKitty as Inspect<u64>>::owner(&(), &item), Some(2));
+        assert!(PalletKitty::kitty_owner(1).is_empty());
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::create_auction(RuntimeOrigin::signed(2), item, 10, 5));
+        assert!(!<PalletKitty as Inspect<u64>>::can_transfer(&(), &item));
+        assert_noop!(<PalletKitty as Transfer<u64>>::transfer(&(), &item, &1), Error::<Test>::KittyInAuction);
+    })
+}
+
+#[test]
+fn burn_kitty_should_clean_up() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+        let kitty_id = PalletKitty::kitty_owner(owner)[0];
+
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(1), kitty_id, 30, 10));
+        assert_ok!(PalletKitty::approve(RuntimeOrigin::signed(owner), kitty_id, 2));
+        assert_eq!(Balance::reserved_balance(1), 30);
+
+        assert_noop!(PalletKitty::burn(RuntimeOrigin::signed(2), kitty_id), Error::<Test>::NotKittyOwner);
+        assert_ok!(PalletKitty::burn(RuntimeOrigin::signed(owner), kitty_id));
+        System::assert_last_event(Event::KittyBurned(owner, kitty_id).into());
+
+        assert!(PalletKitty::kitties(kitty_id).is_none());
+        assert_eq!(PalletKitty::kitty_owner(owner).len(), 1);
+        assert_eq!(PalletKitty::count(), 2);
+        assert!(PalletKitty::kitty_approval(kitty_id).is_none());
+        assert_eq!(PalletKitty::account_offer_count(1), 0);
+        assert_eq!(Balance::reserved_balance(1), 0);
+
+        // the freed slot can be used again
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(owner)));
+    })
+}
+
+#[test]
+fn burn_should_free_the_expiry_slots_of_its_offers() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        // fill the four slots of block 10, two of them on kitty 0
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(2), 0, 10, 10));
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(5), 0, 10, 10));
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(2), 1, 10, 10));
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(5), 1, 10, 10));
+        assert_noop!(PalletKitty::make_offer(RuntimeOrigin::signed(1), 2, 10, 10), Error::<Test>::TooManyOffersExpiring);
+
+        assert_ok!(PalletKitty::burn(RuntimeOrigin::signed(3), 0));
+        assert_eq!(PalletKitty::offers_expiring(10).len(), 2);
+        assert_eq!(Balance::reserved_balance(2), 10);
+        assert_ok!(PalletKitty::make_offer(RuntimeOrigin::signed(1), 2, 10, 10));
+    })
+}
+
+#[test]
+fn burn_should_clear_hash_and_children() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+        let kitties = PalletKitty::kitty_owner(owner);
+        let (male, female) = (kitties[0], kitties[1]);
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, female));
+        let child_id = PalletKitty::kitty_owner(owner)[2];
+        let child_hash = PalletKitty::kitty_hash(child_id).unwrap();
+        assert_eq!(PalletKitty::kitty_id_by_hash(child_hash), Some(child_id));
+
+        assert_ok!(PalletKitty::burn(RuntimeOrigin::signed(owner), male));
+        assert!(PalletKitty::children(male).is_empty());
+        assert_eq!(PalletKitty::children(female).into_inner(), vec![child_id]);
+
+        assert_ok!(PalletKitty::burn(RuntimeOrigin::signed(owner), child_id));
+        assert!(PalletKitty::children(female).is_empty());
+        assert!(PalletKitty::kitty_hash(child_id).is_none());
+        assert!(PalletKitty::kitty_id_by_hash(child_hash).is_none());
+    })
+}
+
+#[test]
+fn force_burn_should_require_root() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let kitty_id = PalletKitty::kitty_owner(4)[0];
+
+        assert_noop!(PalletKitty::force_burn(RuntimeOrigin::signed(4), kitty_id), sp_runtime::DispatchError::BadOrigin);
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::create_auction(RuntimeOrigin::signed(4), kitty_id, 10, 5));
+        assert_ok!(PalletKitty::bid(RuntimeOrigin::signed(5), kitty_id, 20));
+        assert_noop!(PalletKitty::burn(RuntimeOrigin::signed(4), kitty_id), Error::<Test>::KittyInAuction);
+
+        // root ends the auction and refunds the bid
+        assert_ok!(PalletKitty::force_burn(RuntimeOrigin::root(), kitty_id));
+        System::assert_has_event(Event::AuctionBidRefunded(kitty_id, 5, 20).into());
+        assert_eq!(Balance::reserved_balance(5), 0);
+        assert!(PalletKitty::auctions(kitty_id).is_none());
+        assert!(PalletKitty::auctions_ending(6).is_empty());
+        assert!(PalletKitty::kitty_owner(4).is_empty());
+    })
+}
+
+#[test]
+fn kitties_should_get_sequential_indexes() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let sender = 1;
+
+        assert_eq!(PalletKitty::next_kitty_index(), 3);
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(sender)));
+        System::assert_last_event(Event::KittyCreated(sender, 3).into());
+
+        // kitties minted in the same block still get their own dna
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(sender)));
+        assert_ne!(PalletKitty::kitties(3).unwrap().dna(), PalletKitty::kitties(4).unwrap().dna());
+
+        assert_eq!(PalletKitty::kitty_owner(sender).into_inner(), vec![3, 4]);
+        assert_eq!(PalletKitty::kitties(4).unwrap().index(), 4);
+        assert_eq!(PalletKitty::next_kitty_index(), 5);
+
+        // burned indexes are not reused
+        assert_ok!(PalletKitty::burn(RuntimeOrigin::signed(sender), 4));
+        System::set_block_number(3);
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(sender)));
+        assert_eq!(PalletKitty::kitty_owner(sender).into_inner(), vec![3, 5]);
+    })
+}
+
+#[test]
+fn migrate_to_v1_should_index_kitties() {
+    use crate::migrations::{v1::MigrateToV1, v2::MigrateToV2, v3::MigrateToV3};
+    use frame_support::{storage_alias, traits::{OnRuntimeUpgrade, StorageVersion}, Blake2_128Concat};
+    use sp_core::H256;
+
+    // the genesis encoding: dna, owner, price, gender, created date
+    #[storage_alias]
+    type OldKitties = StorageMap<PalletKitty, Blake2_128Concat, H256, ([u8; 16], u64, Option<u64>, Gender, u64)>;
+    #[storage_alias]
+    type OldKittyOwner = StorageMap<PalletKitty, Blake2_128Concat, u64, Vec<H256>>;
+
+    new_test_ext().execute_with(|| {
+        assert_eq!(StorageVersion::get::<PalletKitty>(), 3);
+
+        // start from a chain with only hash keyed kitties
+        let _ = crate::Kitties::<Test>::clear(u32::MAX, None);
+        let _ = crate::KittyOwner::<Test>::clear(u32::MAX, None);
+        crate::NextKittyIndex::<Test>::kill();
+        StorageVersion::new(0).put::<PalletKitty>();
+
+        let (a, b, child) = (H256::repeat_byte(1), H256::repeat_byte(2), H256::repeat_byte(3));
+        for (kitty_hash, created_date) in [(child, 3), (a, 1), (b, 2)] {
+            OldKitties::insert(kitty_hash, ([0; 16], 1, Some(10), Gender::MALE, created_date));
+        }
+        OldKittyOwner::insert(1, vec![child, a, b]);
+
+        MigrateToV1::<Test>::on_runtime_upgrade();
+        assert_eq!(StorageVersion::get::<PalletKitty>(), 1);
+
+        MigrateToV2::<Test>::on_runtime_upgrade();
+        assert_eq!(StorageVersion::get::<PalletKitty>(), 2);
+
+        MigrateToV3::<Test>::on_runtime_upgrade();
+        assert_eq!(StorageVersion::get::<PalletKitty>(), 3);
+
+        assert_eq!(PalletKitty::next_kitty_index(), 3);
+        assert_eq!(PalletKitty::count(), 3);
+        assert_eq!(PalletKitty::kitty_owner(1).into_inner(), vec![0, 1, 2]);
+        assert_eq!(PalletKitty::kitty_id_by_hash(child), Some(2));
+
+        // the old layout has no parents to carry over
+        let kitty = PalletKitty::kitties(2).unwrap();
+        assert_eq!(kitty.parents(), None);
+        assert_eq!(kitty.price(), Some((PriceCurrency::Native, 10)));
+        assert_eq!(kitty.creator(), 1);
+        assert!(kitty.name().is_empty());
+        assert_eq!(kitty.generation(), 0);
+        assert!(PalletKitty::children(0).is_empty());
+
+        // running them again is a no-op
+        MigrateToV1::<Test>::on_runtime_upgrade();
+        MigrateToV2::<Test>::on_runtime_upgrade();
+        MigrateToV3::<Test>::on_runtime_upgrade();
+        assert_eq!(PalletKitty::count(), 3);
+        assert_eq!(PalletKitty::kitty_owner(1).len(), 3);
+    })
+}
+
+#[test]
+fn kitty_metadata_should_reserve_deposit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+        let kitty_id = PalletKitty::kitty_owner(owner)[0];
+
+        assert_noop!(
+            PalletKitty::set_kitty_metadata(RuntimeOrigin::signed(owner), kitty_id, b"a very long name".to_vec(), vec![]),
+            Error::<Test>::NameTooLong
+        );
+        assert_noop!(
+            PalletKitty::set_kitty_metadata(RuntimeOrigin::signed(4), kitty_id, b"tom".to_vec(), vec![]),
+            Error::<Test>::NotKittyOwner
+        );
+
+        assert_ok!(PalletKitty::set_kitty_metadata(RuntimeOrigin::signed(owner), kitty_id, b"tom".to_vec(), b"ipfs://x".to_vec()));
+        System::assert_last_event(Event::MetadataSet(kitty_id, 11).into());
+        assert_eq!(Balance::reserved_balance(owner), 11);
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().name(), b"tom".to_vec());
+        assert_eq!(PalletKitty::kitty_info(&kitty_id).unwrap().name, b"tom".to_vec());
+
+        // a new name replaces the deposit
+        assert_ok!(PalletKitty::set_kitty_metadata(RuntimeOrigin::signed(owner), kitty_id, b"felix".to_vec(), vec![]));
+        assert_eq!(Balance::reserved_balance(owner), 5);
+
+        assert_ok!(PalletKitty::clear_kitty_metadata(RuntimeOrigin::signed(owner), kitty_id));
+        assert_eq!(Balance::reserved_balance(owner), 0);
+        assert!(PalletKitty::kitties(kitty_id).unwrap().name().is_empty());
+        assert_noop!(PalletKitty::clear_kitty_metadata(RuntimeOrigin::signed(owner), kitty_id), Error::<Test>::MetadataNotExists);
+    })
+}
+
+#[test]
+fn kitty_metadata_should_be_refunded_on_transfer_and_burn() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+        let kitties = PalletKitty::kitty_owner(owner);
+
+        assert_ok!(PalletKitty::set_kitty_metadata(RuntimeOrigin::signed(owner), kitties[0], b"tom".to_vec(), vec![]));
+        assert_ok!(PalletKitty::set_kitty_metadata(RuntimeOrigin::signed(owner), kitties[1], b"felix".to_vec(), vec![]));
+        assert_eq!(Balance::reserved_balance(owner), 8);
+
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(owner), 4, kitties[0]));
+        assert_eq!(Balance::reserved_balance(owner), 5);
+        assert!(PalletKitty::kitties(kitties[0]).unwrap().name().is_empty());
+        assert!(PalletKitty::metadata(kitties[0]).is_none());
+
+        assert_ok!(PalletKitty::burn(RuntimeOrigin::signed(owner), kitties[1]));
+        assert_eq!(Balance::reserved_balance(owner), 0);
+        assert!(PalletKitty::metadata(kitties[1]).is_none());
+    })
+}
+
+#[test]
+fn traits_should_be_decoded_from_dna() {
+    use crate::{EyeColor, FurColor, KittyTraits, Pattern, Rarity};
+
+    let common = KittyTraits::from_dna(&[0; 16]);
+    assert_eq!(common.fur_color, FurColor::Black);
+    assert_eq!(common.eye_color, EyeColor::Green);
+    assert_eq!(common.pattern, Pattern::Solid);
+    assert_eq!(common.rarity_score, 3);
+    assert_eq!(common.rarity, Rarity::Common);
+
+    let mut dna = [0; 16];
+    dna[1] = 200;
+    dna[2] = 255;
+    dna[3] = 130;
+    let rare = KittyTraits::from_dna(&dna);
+    assert_eq!(rare.fur_color, FurColor::Ginger);
+    assert_eq!(rare.eye_color, EyeColor::Odd);
+    assert_eq!(rare.pattern, Pattern::Tabby);
+    assert_eq!(rare.rarity_score, 4 + 16 + 2);
+    assert_eq!(rare.rarity, Rarity::Epic);
+
+    // a mutation doubles the score
+    dna[4] = 255;
+    assert_eq!(KittyTraits::from_dna(&dna).rarity_score, 44);
+    assert_eq!(KittyTraits::from_dna(&dna).rarity, Rarity::Legendary);
+
+    new_test_ext().execute_with(|| {
+        let kitty_id = PalletKitty::kitty_owner(3)[0];
+        assert_eq!(PalletKitty::kitty_traits(&kitty_id), Some(common.clone()));
+        assert_eq!(PalletKitty::kitty_info(&kitty_id).unwrap().traits, common);
+    })
+}
+
+#[test]
+fn breeding_should_track_lineage() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+        let (male, female) = (0, 1);
+        assert_eq!(PalletKitty::kitties(male).unwrap().generation(), 0);
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, female));
+        let child = PalletKitty::next_kitty_index() - 1;
+        assert_eq!(PalletKitty::kitties(child).unwrap().generation(), 1);
+        assert_eq!(PalletKitty::children(male).into_inner(), vec![child]);
+        assert_eq!(PalletKitty::children(female).into_inner(), vec![child]);
+        assert_eq!(PalletKitty::ancestry(&child, 8), vec![(male, 1), (female, 1)]);
+
+        // kitty 2 bred from the child and its father
+        crate::Kitties::<Test>::mutate(2, |kitty| kitty.as_mut().unwrap().set_parents((child, male)));
+        assert_eq!(PalletKitty::ancestry(&2, 8), vec![(child, 1), (male, 1), (female, 2)]);
+        assert_eq!(PalletKitty::ancestry(&2, 1), vec![(child, 1), (male, 1)]);
+
+        // the number of children per parent is bounded
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(owner), 5, child));
+        System::set_block_number(2);
+        pass_time(ACTION_COOLDOWN);
+        assert_ok!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, female));
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(owner), 5, child + 1));
+        System::set_block_number(3);
+        pass_time(ACTION_COOLDOWN);
+        assert_noop!(PalletKitty::breed(RuntimeOrigin::signed(owner), male, female), Error::<Test>::TooManyChildren);
+    })
+}
+
+#[test]
+fn kittens_should_not_be_listed() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 1;
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(owner)));
+        let kitty_id = PalletKitty::kitty_owner(owner)[0];
+
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().life_stage(Timestamp::now()), LifeStage::Kitten);
+        assert_noop!(PalletKitty::set_price(RuntimeOrigin::signed(owner), kitty_id, Some((PriceCurrency::Native, 10))), Error::<Test>::KittyTooYoung);
+        assert_noop!(PalletKitty::list_dutch(RuntimeOrigin::signed(owner), kitty_id, 90, 10, 8), Error::<Test>::KittyTooYoung);
+        assert_noop!(PalletKitty::create_auction(RuntimeOrigin::signed(owner), kitty_id, 10, 5), Error::<Test>::KittyTooYoung);
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(owner), kitty_id, None));
+
+        pass_time(ADULT_AGE);
+        assert_eq!(PalletKitty::kitty_info(&kitty_id).unwrap().life_stage, LifeStage::Adult);
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(owner), kitty_id, Some((PriceCurrency::Native, 10))));
+
+        pass_time(ELDER_AGE);
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().life_stage(Timestamp::now()), LifeStage::Elder);
+    })
+}
+
+#[test]
+fn transfers_should_start_a_cooldown() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        pass_time(ADULT_AGE);
+        let kitty_id = PalletKitty::kitty_owner(3)[0];
+
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(3), 1, kitty_id));
+        assert_eq!(PalletKitty::next_action_at(kitty_id), Some(ADULT_AGE + ACTION_COOLDOWN));
+        assert_noop!(PalletKitty::transfer(RuntimeOrigin::signed(1), 2, kitty_id), Error::<Test>::KittyInCooldown);
+
+        // the new owner can list the kitty but not sell it yet
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(1), kitty_id, Some((PriceCurrency::Native, 10))));
+        assert_noop!(PalletKitty::buy(RuntimeOrigin::signed(2), kitty_id), Error::<Test>::KittyInCooldown);
+
+        pass_time(ACTION_COOLDOWN);
+        assert_ok!(PalletKitty::buy(RuntimeOrigin::signed(2), kitty_id));
+        assert_noop!(PalletKitty::transfer(RuntimeOrigin::signed(2), 1, kitty_id), Error::<Test>::KittyInCooldown);
+    })
+}
+
+#[test]
+fn breed_with_sire_should_pay_sire_owner() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        pass_time(ADULT_AGE);
+        let (sire_owner, breeder) = (4, 3);
+        let sire = PalletKitty::kitty_owner(sire_owner)[0];
+        let matron = 1;
+        assert_eq!(PalletKitty::kitties(matron).unwrap().gender(), Gender::FEMALE);
+
+        assert_noop!(PalletKitty::offer_sire(RuntimeOrigin::signed(breeder), matron, 10), Error::<Test>::SireNotMale);
+        assert_noop!(PalletKitty::breed_with_sire(RuntimeOrigin::signed(breeder), matron, sire), Error::<Test>::SireNotOffered);
+
+        assert_ok!(PalletKitty::offer_sire(RuntimeOrigin::signed(sire_owner), sire, 10));
+        System::assert_last_event(Event::SireOffered(sire, 10).into());
+
+        assert_noop!(PalletKitty::breed_with_sire(RuntimeOrigin::signed(breeder), 0, sire), Error::<Test>::MatronNotFemale);
+        assert_noop!(PalletKitty::breed_with_sire(RuntimeOrigin::signed(sire_owner), matron, sire), Error::<Test>::NotKittyOwner);
+
+        assert_ok!(PalletKitty::breed_with_sire(RuntimeOrigin::signed(breeder), matron, sire));
+        let child = PalletKitty::next_kitty_index() - 1;
+        System::assert_last_event(Event::BredWithSire(breeder, child, matron, sire, 10).into());
+
+        assert_eq!(PalletKitty::kitties(child).unwrap().owner(), breeder);
+        assert_eq!(PalletKitty::kitties(child).unwrap().parents(), Some((matron, sire)));
+        assert_eq!(PalletKitty::kitties(sire).unwrap().owner(), sire_owner);
+        assert_eq!(Balance::free_balance(breeder), 90);
+        assert_eq!(Balance::free_balance(sire_owner), 110);
+
+        // both parents rest before the next breeding
+        assert_noop!(PalletKitty::breed_with_sire(RuntimeOrigin::signed(breeder), matron, sire), Error::<Test>::KittyInCooldown);
+        assert_noop!(PalletKitty::transfer(RuntimeOrigin::signed(sire_owner), 5, sire), Error::<Test>::KittyInCooldown);
+
+        pass_time(ACTION_COOLDOWN);
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(sire_owner), 5, sire));
+        assert!(PalletKitty::sire_offers(sire).is_none());
+    })
+}
+
+#[test]
+fn leased_kitty_should_be_locked_until_expiry() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        pass_time(ADULT_AGE);
+        let (owner, user) = (3, 5);
+        let kitty_id = 0;
+
+        assert_noop!(PalletKitty::lease(RuntimeOrigin::signed(owner), kitty_id, owner, 10, 5), Error::<Test>::LeaseToSelf);
+        assert_noop!(PalletKitty::lease(RuntimeOrigin::signed(owner), kitty_id, user, 101, 5), Error::<Test>::InvalidLeaseDuration);
+
+        assert_ok!(PalletKitty::lease(RuntimeOrigin::signed(owner), kitty_id, user, 10, 5));
+        System::assert_last_event(Event::LeaseOffered(kitty_id, user, 5, 10).into());
+        assert_noop!(PalletKitty::lease(RuntimeOrigin::signed(owner), kitty_id, 4, 10, 5), Error::<Test>::LeaseOfferExists);
+
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(1)));
+        let kitten = PalletKitty::kitty_owner(1)[0];
+        assert_noop!(PalletKitty::lease(RuntimeOrigin::signed(1), kitten, user, 10, 5), Error::<Test>::KittyTooYoung);
+
+        assert_noop!(PalletKitty::accept_lease(RuntimeOrigin::signed(4), kitty_id, 5), Error::<Test>::LeaseOfferNotExists);
+        assert_noop!(PalletKitty::accept_lease(RuntimeOrigin::signed(user), kitty_id, 4), Error::<Test>::LeaseFeeTooHigh);
+
+        assert_ok!(PalletKitty::accept_lease(RuntimeOrigin::signed(user), kitty_id, 5));
+        System::assert_last_event(Event::KittyLeased(kitty_id, user, 5, 11).into());
+        assert_eq!(PalletKitty::user_of(&kitty_id), Some(user));
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), owner);
+        assert_eq!(Balance::free_balance(owner), 105);
+        assert_eq!(Balance::free_balance(user), 95);
+
+        assert_noop!(PalletKitty::transfer(RuntimeOrigin::signed(owner), 4, kitty_id), Error::<Test>::KittyLeased);
+        assert_noop!(PalletKitty::set_price(RuntimeOrigin::signed(owner), kitty_id, Some((PriceCurrency::Native, 10))), Error::<Test>::KittyLeased);
+        assert_noop!(PalletKitty::burn(RuntimeOrigin::signed(owner), kitty_id), Error::<Test>::KittyLeased);
+
+        run_to_block(10);
+        assert_eq!(PalletKitty::user_of(&kitty_id), Some(user));
+
+        run_to_block(11);
+        System::assert_last_event(Event::LeaseEnded(kitty_id, user).into());
+        assert_eq!(PalletKitty::user_of(&kitty_id), None);
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(owner), 4, kitty_id));
+    })
+}
+
+#[test]
+fn lease_offer_should_be_cleared_on_transfer() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (owner, user) = (3, 5);
+        let kitty_id = 0;
+
+        assert_ok!(PalletKitty::lease(RuntimeOrigin::signed(owner), kitty_id, user, 10, 0));
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(owner), 4, kitty_id));
+
+        assert!(PalletKitty::lease_offers(kitty_id).is_none());
+        assert_noop!(PalletKitty::accept_lease(RuntimeOrigin::signed(user), kitty_id, 0), Error::<Test>::LeaseOfferNotExists);
+    })
+}
+
+#[test]
+fn swap_should_exchange_kitties_and_top_up() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (proposer, acceptor) = (3, 4);
+        let (offered, wanted) = (0, 2);
+
+        assert_noop!(PalletKitty::propose_swap(RuntimeOrigin::signed(proposer), offered, 1, None, 10), Error::<Test>::SwapWithSelf);
+        assert_noop!(PalletKitty::propose_swap(RuntimeOrigin::signed(proposer), offered, wanted, None, 1), Error::<Test>::SwapExpired);
+
+        pass_time(ADULT_AGE);
+        assert_ok!(PalletKitty::create_auction(RuntimeOrigin::signed(acceptor), wanted, 10, 5));
+        assert_noop!(PalletKitty::propose_swap(RuntimeOrigin::signed(proposer), offered, wanted, None, 10), Error::<Test>::KittyInAuction);
+        run_to_block(6);
+
+        assert_ok!(PalletKitty::propose_swap(RuntimeOrigin::signed(proposer), offered, wanted, Some(10), 10));
+        System::assert_last_event(Event::SwapProposed(0, proposer, offered, wanted, Some(10), 10).into());
+        assert_eq!(Balance::reserved_balance(proposer), 10);
+
+        assert_noop!(PalletKitty::accept_swap(RuntimeOrigin::signed(5), 0), Error::<Test>::NotKittyOwner);
+        assert_noop!(PalletKitty::cancel_swap(RuntimeOrigin::signed(acceptor), 0), Error::<Test>::NotSwapProposer);
+
+        assert_ok!(PalletKitty::accept_swap(RuntimeOrigin::signed(acceptor), 0));
+        System::assert_has_event(Event::SwapAccepted(0, acceptor).into());
+
+        assert_eq!(PalletKitty::kitties(offered).unwrap().owner(), acceptor);
+        assert_eq!(PalletKitty::kitties(wanted).unwrap().owner(), proposer);
+        assert_eq!(Balance::reserved_balance(proposer), 0);
+        assert_eq!(Balance::free_balance(proposer), 90);
+        assert_eq!(Balance::free_balance(acceptor), 110);
+        assert!(PalletKitty::swaps(0).is_none());
+        assert!(PalletKitty::kitty_swaps(offered).is_empty());
+    })
+}
+
+#[test]
+fn swap_should_be_invalidated_on_transfer_and_expire() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (proposer, acceptor) = (3, 4);
+
+        assert_ok!(PalletKitty::propose_swap(RuntimeOrigin::signed(proposer), 0, 2, Some(10), 10));
+        assert_ok!(PalletKitty::propose_swap(RuntimeOrigin::signed(proposer), 1, 2, None, 5));
+
+        // the wanted kitty changes hands
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(acceptor), 5, 2));
+        System::assert_has_event(Event::SwapInvalidated(0).into());
+        System::assert_has_event(Event::SwapInvalidated(1).into());
+        assert_eq!(Balance::reserved_balance(proposer), 0);
+        assert_noop!(PalletKitty::accept_swap(RuntimeOrigin::signed(5), 0), Error::<Test>::SwapNotExists);
+
+        // the wanted kitty rests after changing hands
+        assert_noop!(PalletKitty::propose_swap(RuntimeOrigin::signed(proposer), 0, 2, Some(10), 5), Error::<Test>::KittyInCooldown);
+        pass_time(ACTION_COOLDOWN);
+        assert_ok!(PalletKitty::propose_swap(RuntimeOrigin::signed(proposer), 0, 2, Some(10), 5));
+        run_to_block(5);
+        System::assert_last_event(Event::SwapExpired(2).into());
+        assert_eq!(Balance::reserved_balance(proposer), 0);
+        assert!(PalletKitty::kitty_swaps(0).is_empty());
+    })
+}
+
+#[test]
+fn closed_swaps_should_free_their_expiry_slot() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(5)));
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(1)));
+        let (kitty_of_5, kitty_of_1) = (PalletKitty::kitty_owner(5)[0], PalletKitty::kitty_owner(1)[0]);
+
+        // fill the four slots of block 10
+        assert_ok!(PalletKitty::propose_swap(RuntimeOrigin::signed(3), 0, 2, None, 10));
+        assert_ok!(PalletKitty::propose_swap(RuntimeOrigin::signed(3), 1, 2, None, 10));
+        assert_ok!(PalletKitty::propose_swap(RuntimeOrigin::signed(5), kitty_of_5, 0, None, 10));
+        assert_ok!(PalletKitty::propose_swap(RuntimeOrigin::signed(1), kitty_of_1, 1, None, 10));
+        assert_noop!(PalletKitty::propose_swap(RuntimeOrigin::signed(1), kitty_of_1, kitty_of_5, None, 10), Error::<Test>::TooManySwapsExpiring);
+
+        assert_ok!(PalletKitty::cancel_swap(RuntimeOrigin::signed(5), 2));
+        assert_eq!(PalletKitty::swaps_expiring(10).into_inner(), vec![0, 1, 3]);
+        assert_ok!(PalletKitty::propose_swap(RuntimeOrigin::signed(1), kitty_of_1, kitty_of_5, None, 10));
+
+        // accepting also invalidates the other swaps of both kitties
+        assert_ok!(PalletKitty::accept_swap(RuntimeOrigin::signed(4), 1));
+        assert_eq!(PalletKitty::swaps_expiring(10).into_inner(), vec![4]);
+    })
+}
+
+#[test]
+fn bundle_should_be_sold_as_one_lot() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        pass_time(ADULT_AGE);
+        let (seller, buyer) = (3, 4);
+        let kitty_ids: frame_support::BoundedVec<_, _> = vec![0, 1].try_into().unwrap();
+
+        assert_noop!(PalletKitty::list_bundle(RuntimeOrigin::signed(seller), vec![0, 0].try_into().unwrap(), 40), Error::<Test>::DuplicateKittyInBundle);
+        assert_noop!(PalletKitty::list_bundle(RuntimeOrigin::signed(seller), vec![0, 2].try_into().unwrap(), 40), Error::<Test>::NotKittyOwner);
+
+        assert_ok!(PalletKitty::list_bundle(RuntimeOrigin::signed(seller), kitty_ids, 40));
+        System::assert_last_event(Event::BundleListed(0, seller, 40).into());
+
+        // kitties in the bundle can not be sold on their own
+        assert_noop!(PalletKitty::set_price(RuntimeOrigin::signed(seller), 0, Some((PriceCurrency::Native, 10))), Error::<Test>::KittyInBundle);
+        assert_noop!(PalletKitty::transfer(RuntimeOrigin::signed(seller), 5, 1), Error::<Test>::KittyInBundle);
+
+        assert_ok!(PalletKitty::buy_bundle(RuntimeOrigin::signed(buyer), 0));
+        System::assert_last_event(Event::BundleSold(0, seller, buyer, 40).into());
+
+        assert_eq!(PalletKitty::kitties(0).unwrap().owner(), buyer);
+        assert_eq!(PalletKitty::kitties(1).unwrap().owner(), buyer);
+        assert_eq!(Balance::free_balance(buyer), 60);
+        assert_eq!(Balance::free_balance(seller), 136);
+        assert_eq!(Balance::free_balance(FEE_ACCOUNT), 4);
+        assert!(PalletKitty::kitty_bundle(0).is_none());
+        assert_noop!(PalletKitty::buy_bundle(RuntimeOrigin::signed(buyer), 0), Error::<Test>::BundleNotExists);
+    })
+}
+
+#[test]
+fn bundle_should_respect_buyer_kitty_limit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        pass_time(ADULT_AGE);
+        let (seller, buyer) = (3, 4);
+
+        assert_ok!(PalletKitty::create_kitty(RuntimeOrigin::signed(buyer)));
+        assert_ok!(PalletKitty::list_bundle(RuntimeOrigin::signed(seller), vec![0, 1].try_into().unwrap(), 40));
+
+        assert_noop!(PalletKitty::buy_bundle(RuntimeOrigin::signed(buyer), 0), Error::<Test>::MaxOwnerKitty);
+        assert_noop!(PalletKitty::cancel_bundle(RuntimeOrigin::signed(buyer), 0), Error::<Test>::NotBundleSeller);
+
+        assert_ok!(PalletKitty::cancel_bundle(RuntimeOrigin::signed(seller), 0));
+        System::assert_last_event(Event::BundleCancelled(0).into());
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), 0, Some((PriceCurrency::Native, 10))));
+    })
+}
+
+#[test]
+fn kitty_should_be_bought_with_accepted_asset() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        pass_time(ADULT_AGE);
+        let (seller, buyer) = (3, 4);
+        let kitty_id = 0;
+
+        assert_noop!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Asset(ASSET_ID), 20))), Error::<Test>::AssetNotAccepted);
+        assert_noop!(PalletKitty::set_accepted_asset(RuntimeOrigin::signed(seller), ASSET_ID, true), sp_runtime::DispatchError::BadOrigin);
+
+        assert_ok!(PalletKitty::set_accepted_asset(RuntimeOrigin::root(), ASSET_ID, true));
+        System::assert_last_event(Event::AcceptedAssetSet(ASSET_ID, true).into());
+
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Native, 50))));
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Asset(ASSET_ID), 20))));
+        System::assert_last_event(Event::PriceSet(kitty_id, Some((PriceCurrency::Asset(ASSET_ID), 20))).into());
+
+        assert_ok!(PalletKitty::buy(RuntimeOrigin::signed(buyer), kitty_id));
+        System::assert_last_event(Event::KittySoldForAsset(kitty_id, seller, buyer, ASSET_ID, 20, 0).into());
+
+        // the fee account has no asset account yet, 2 is above the minimum balance
+        assert_eq!(Assets::balance(ASSET_ID, buyer), 80);
+        assert_eq!(Assets::balance(ASSET_ID, seller), 18);
+        assert_eq!(Assets::balance(ASSET_ID, FEE_ACCOUNT), 2);
+        assert_eq!(Balance::free_balance(buyer), 100);
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), buyer);
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().price(), None);
+    })
+}
+
+#[test]
+fn fee_below_the_minimum_of_an_empty_fee_account_should_be_reported() {
+    use frame_support::traits::fungibles::{Create, Mutate};
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        pass_time(ADULT_AGE);
+        let (seller, buyer, kitty_id, asset_id) = (3, 4, 0, 8);
+
+        // the fee account holds none of an asset with a minimum balance of 5
+        assert_ok!(<Assets as Create<u64>>::create(asset_id, 1, true, 5));
+        assert_ok!(<Assets as Mutate<u64>>::mint_into(asset_id, &buyer, 100));
+        assert_ok!(PalletKitty::set_accepted_asset(RuntimeOrigin::root(), asset_id, true));
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Asset(asset_id), 20))));
+
+        assert_ok!(PalletKitty::buy(RuntimeOrigin::signed(buyer), kitty_id));
+        System::assert_has_event(Event::MarketplaceFeeSkipped(kitty_id, FEE_ACCOUNT, 2).into());
+
+        assert_eq!(Assets::balance(asset_id, seller), 20);
+        assert_eq!(Assets::balance(asset_id, FEE_ACCOUNT), 0);
+    })
+}
+
+#[test]
+fn asset_priced_kitties_should_be_listed() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        pass_time(ADULT_AGE);
+        let seller = 3;
+
+        assert_ok!(PalletKitty::set_accepted_asset(RuntimeOrigin::root(), ASSET_ID, true));
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), 0, Some((PriceCurrency::Native, 50))));
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), 1, Some((PriceCurrency::Asset(ASSET_ID), 20))));
+
+        let mut listed: Vec<_> = PalletKitty::listed_kitties(0, 10)
+            .into_iter()
+            .map(|info| (info.id, info.price, info.price_asset))
+            .collect();
+        listed.sort();
+        assert_eq!(listed, vec![(0, Some(50), None), (1, Some(20), Some(ASSET_ID))]);
+
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), 1, None));
+        assert_eq!(PalletKitty::listed_kitties(0, 10).len(), 1);
+    })
+}
+
+#[test]
+fn asset_price_should_need_the_asset_to_stay_accepted() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        pass_time(ADULT_AGE);
+        let (seller, buyer) = (3, 4);
+        let kitty_id = 0;
+
+        assert_ok!(PalletKitty::set_accepted_asset(RuntimeOrigin::root(), ASSET_ID, true));
+        assert_ok!(PalletKitty::set_price(RuntimeOrigin::signed(seller), kitty_id, Some((PriceCurrency::Asset(ASSET_ID), 200))));
+
+        assert_noop!(PalletKitty::buy(RuntimeOrigin::signed(buyer), kitty_id), Error::<Test>::NotEnoughMoney);
+
+        assert_ok!(PalletKitty::set_accepted_asset(RuntimeOrigin::root(), ASSET_ID, false));
+        assert_noop!(PalletKitty::buy(RuntimeOrigin::signed(buyer), kitty_id), Error::<Test>::AssetNotAccepted);
+    })
+}
+
+#[test]
+fn fractionalized_kitty_should_be_redeemed_with_all_shares() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+        let kitty_id = 0;
+
+        assert_noop!(PalletKitty::fractionalize(RuntimeOrigin::signed(owner), kitty_id, 0, 60), Error::<Test>::InvalidShares);
+        assert_noop!(PalletKitty::fractionalize(RuntimeOrigin::signed(owner), kitty_id, 100, 0), Error::<Test>::InvalidBuyoutPrice);
+
+        // the next share asset id is taken in pallet_assets directly
+        assert_ok!(<Assets as frame_support::traits::fungibles::Create<u64>>::create(0, 1, true, 1));
+
+        assert_ok!(PalletKitty::fractionalize(RuntimeOrigin::signed(owner), kitty_id, 100, 60));
+        let asset_id = PalletKitty::kitty_vault(kitty_id).unwrap();
+        assert_eq!(asset_id, 1);
+        System::assert_last_event(Event::KittyFractionalized(kitty_id, owner, asset_id, 100, 60).into());
+
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), PalletKitty::vault_account(asset_id));
+        assert_eq!(Assets::balance(asset_id, owner), 100);
+
+        assert_ok!(Assets::transfer(RuntimeOrigin::signed(owner), asset_id, 5, 1));
+        assert_noop!(PalletKitty::redeem(RuntimeOrigin::signed(owner), asset_id), Error::<Test>::NotAllShares);
+        assert_ok!(Assets::transfer(RuntimeOrigin::signed(5), asset_id, owner, 1));
+
+        pass_time(ACTION_COOLDOWN);
+        assert_ok!(PalletKitty::redeem(RuntimeOrigin::signed(owner), asset_id));
+        System::assert_last_event(Event::KittyRedeemed(kitty_id, owner, asset_id).into());
+
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), owner);
+        assert_eq!(Assets::balance(asset_id, owner), 0);
+        assert!(PalletKitty::vaults(asset_id).is_none());
+        assert!(PalletKitty::kitty_vault(kitty_id).is_none());
+    })
+}
+
+#[test]
+fn fractionalize_should_fail_when_no_free_asset_id_is_found() {
+    use frame_support::traits::fungibles::Create;
+
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+
+        // every id up to the probe bound is taken in pallet_assets directly
+        for asset_id in (0..pallet_token::MAX_ID_PROBES).filter(|asset_id| *asset_id != ASSET_ID) {
+            assert_ok!(<Assets as Create<u64>>::create(asset_id, 1, true, 1));
+        }
+
+        assert_noop!(PalletKitty::fractionalize(RuntimeOrigin::signed(3), 0, 100, 60), pallet_token::Error::<Test>::NoFreeAssetId);
+    })
+}
+
+#[test]
+fn buyout_should_pay_share_holders_pro_rata() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (owner, holder, buyer) = (3, 5, 4);
+        let kitty_id = 0;
+
+        assert_ok!(PalletKitty::fractionalize(RuntimeOrigin::signed(owner), kitty_id, 100, 60));
+        let asset_id = PalletKitty::kitty_vault(kitty_id).unwrap();
+        assert_ok!(Assets::transfer(RuntimeOrigin::signed(owner), asset_id, holder, 40));
+
+        assert_noop!(PalletKitty::claim_buyout(RuntimeOrigin::signed(holder), asset_id), Error::<Test>::VaultNotBoughtOut);
+
+        assert_ok!(PalletKitty::buyout(RuntimeOrigin::signed(buyer), asset_id));
+        System::assert_last_event(Event::KittyBoughtOut(kitty_id, buyer, asset_id, 60).into());
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), buyer);
+        assert_noop!(PalletKitty::redeem(RuntimeOrigin::signed(owner), asset_id), Error::<Test>::VaultBoughtOut);
+
+        assert_ok!(PalletKitty::claim_buyout(RuntimeOrigin::signed(holder), asset_id));
+        System::assert_last_event(Event::BuyoutClaimed(asset_id, holder, 24).into());
+        assert_noop!(PalletKitty::claim_buyout(RuntimeOrigin::signed(holder), asset_id), Error::<Test>::NoShares);
+
+        assert_ok!(PalletKitty::claim_buyout(RuntimeOrigin::signed(owner), asset_id));
+        System::assert_last_event(Event::BuyoutClaimed(asset_id, owner, 36).into());
+
+        assert_eq!(Balance::free_balance(buyer), 40);
+        assert_eq!(Balance::free_balance(holder), 124);
+        assert_eq!(Balance::free_balance(owner), 136);
+        assert!(PalletKitty::vaults(asset_id).is_none());
+    })
+}
+
+#[test]
+fn staking_rewards_should_be_shared_by_rarity_weight() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (owner_a, owner_b) = (3, 4);
+
+        assert_noop!(PalletKitty::set_reward_rate(RuntimeOrigin::signed(owner_a), 10), sp_runtime::DispatchError::BadOrigin);
+        assert_ok!(PalletKitty::set_reward_rate(RuntimeOrigin::root(), 10));
+        let asset_id = PalletKitty::reward_asset().unwrap();
+
+        // every genesis kitty has a rarity score of 3
+        assert_ok!(PalletKitty::stake(RuntimeOrigin::signed(owner_a), 0));
+        System::assert_last_event(Event::KittyStaked(0, owner_a, 3).into());
+        assert_ok!(PalletKitty::stake(RuntimeOrigin::signed(owner_b), 2));
+
+        assert_noop!(PalletKitty::transfer(RuntimeOrigin::signed(owner_a), 5, 0), Error::<Test>::KittyStaked);
+        assert_noop!(PalletKitty::claim_rewards(RuntimeOrigin::signed(owner_b), 0), Error::<Test>::NotStaker);
+
+        // 20 rewards over a weight of 6, then 30 over a weight of 9
+        System::set_block_number(3);
+        assert_ok!(PalletKitty::stake(RuntimeOrigin::signed(owner_a), 1));
+        System::set_block_number(6);
+
+        assert_ok!(PalletKitty::claim_rewards(RuntimeOrigin::signed(owner_a), 0));
+        System::assert_last_event(Event::RewardsClaimed(0, owner_a, 19).into());
+        assert_ok!(PalletKitty::claim_rewards(RuntimeOrigin::signed(owner_b), 2));
+        assert_ok!(PalletKitty::unstake(RuntimeOrigin::signed(owner_a), 1));
+        System::assert_has_event(Event::RewardsClaimed(1, owner_a, 9).into());
+
+        // rounding never pays out more than was emitted
+        assert_eq!(Assets::balance(asset_id, owner_a), 28);
+        assert_eq!(Assets::balance(asset_id, owner_b), 19);
+        assert_eq!(PalletKitty::total_stake_weight(), 6);
+
+        // 30 rewards over a weight of 6
+        System::set_block_number(9);
+        assert_ok!(PalletKitty::claim_rewards(RuntimeOrigin::signed(owner_a), 0));
+        System::assert_last_event(Event::RewardsClaimed(0, owner_a, 15).into());
+
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(owner_a), 5, 1));
+    })
+}
+
+#[test]
+fn staking_rewards_should_stay_exact_at_the_rate_bound() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let owner = 3;
+
+        // the rewards of the longest gap between pool updates just fit the accumulator
+        let max_rate = (u128::MAX / REWARD_PRECISION / MAX_BLOCKS_BETWEEN_UPDATES as u128) as u64;
+
+        assert_noop!(PalletKitty::set_reward_rate(RuntimeOrigin::root(), max_rate + 1), Error::<Test>::RewardRateTooHigh);
+        assert_ok!(PalletKitty::set_reward_rate(RuntimeOrigin::root(), max_rate));
+
+        // every genesis kitty has a rarity score of 3
+        assert_ok!(PalletKitty::stake(RuntimeOrigin::signed(owner), 0));
+
+        // the pool is checkpointed once the gap reaches the bound
+        System::set_block_number(MAX_BLOCKS_BETWEEN_UPDATES);
+        PalletKitty::on_initialize(System::block_number());
+        assert_eq!(PalletKitty::last_reward_block(), 1);
+
+        System::set_block_number(MAX_BLOCKS_BETWEEN_UPDATES + 1);
+        PalletKitty::on_initialize(System::block_number());
+        assert_eq!(PalletKitty::last_reward_block(), MAX_BLOCKS_BETWEEN_UPDATES + 1);
+
+        let rewards = max_rate as u128 * MAX_BLOCKS_BETWEEN_UPDATES as u128;
+        assert_eq!(PalletKitty::reward_per_weight(), rewards * REWARD_PRECISION / 3);
+    })
+}
+
+#[test]
+fn repaid_loan_should_unlock_collateral() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (borrower, lender) = (3, 4);
+        let kitty_id = 0;
+
+        assert_noop!(PalletKitty::request_loan(RuntimeOrigin::signed(borrower), kitty_id, 20, 5, 0), Error::<Test>::InvalidLoanDuration);
+
+        assert_ok!(PalletKitty::request_loan(RuntimeOrigin::signed(borrower), kitty_id, 20, 5, 10));
+        System::assert_last_event(Event::LoanRequested(0, borrower, kitty_id, 20, 5, 10).into());
+
+        // the collateral is in escrow
+        assert_noop!(PalletKitty::set_price(RuntimeOrigin::signed(borrower), kitty_id, Some((PriceCurrency::Native, 10))), Error::<Test>::KittyInLoan);
+        assert_noop!(PalletKitty::transfer(RuntimeOrigin::signed(borrower), 5, kitty_id), Error::<Test>::KittyInLoan);
+        assert_noop!(PalletKitty::buy(RuntimeOrigin::signed(5), kitty_id), Error::<Test>::KittyInLoan);
+
+        assert_noop!(PalletKitty::fund_loan(RuntimeOrigin::signed(borrower), 0), Error::<Test>::LendToSelf);
+        assert_noop!(PalletKitty::repay_loan(RuntimeOrigin::signed(borrower), 0), Error::<Test>::LoanNotFunded);
+
+        assert_ok!(PalletKitty::fund_loan(RuntimeOrigin::signed(lender), 0));
+        System::assert_last_event(Event::LoanFunded(0, lender, 11).into());
+        assert_eq!(Balance::free_balance(borrower), 120);
+        assert_noop!(PalletKitty::cancel_loan_request(RuntimeOrigin::signed(borrower), 0), Error::<Test>::LoanFunded);
+
+        assert_ok!(PalletKitty::repay_loan(RuntimeOrigin::signed(borrower), 0));
+        System::assert_last_event(Event::LoanRepaid(0, lender, 25).into());
+        assert_eq!(Balance::free_balance(borrower), 95);
+        assert_eq!(Balance::free_balance(lender), 105);
+
+        assert!(PalletKitty::loans(0).is_none());
+        assert_ok!(PalletKitty::transfer(RuntimeOrigin::signed(borrower), 5, kitty_id));
+    })
+}
+
+#[test]
+fn defaulted_loan_should_let_lender_claim_collateral() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let (borrower, lender) = (3, 4);
+        let kitty_id = 1;
+
+        assert_ok!(PalletKitty::request_loan(RuntimeOrigin::signed(borrower), kitty_id, 20, 5, 10));
+        assert_ok!(PalletKitty::fund_loan(RuntimeOrigin::signed(lender), 0));
+
+        System::set_block_number(11);
+        assert_noop!(PalletKitty::claim_collateral(RuntimeOrigin::signed(lender), 0), Error::<Test>::LoanNotDefaulted);
+
+        System::set_block_number(12);
+        assert_noop!(PalletKitty::claim_collateral(RuntimeOrigin::signed(5), 0), Error::<Test>::NotLender);
+        assert_ok!(PalletKitty::claim_collateral(RuntimeOrigin::signed(lender), 0));
+        System::assert_has_event(Event::CollateralClaimed(0, lender, kitty_id).into());
+
+        assert_eq!(PalletKitty::kitties(kitty_id).unwrap().owner(), lender);
+        assert!(PalletKitty::kitty_loan(kitty_id).is_none());
+        assert_noop!(PalletKitty::repay_loan(RuntimeOrigin::signed(borrower), 0), Error::<Test>::LoanNotExists);
+    })
+}
+
+#[test]
+fn loan_request_should_be_cancelled_before_funding() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(1);
+        let borrower = 3;
+
+        assert_ok!(PalletKitty::request_loan(RuntimeOrigin::signed(borrower), 0, 20, 5, 10));
+        assert_noop!(PalletKitty::cancel_loan_request(RuntimeOrigin::signed(4), 0), Error::<Test>::NotBorrower);
+
+        assert_ok!(PalletKitty::cancel_loan_request(RuntimeOrigin::signed(borrower), 0));
+        System::assert_last_event(Event::LoanCancelled(0).into());
+        assert!(PalletKitty::kitty_loan(0).is_none());
+        assert_noop!(PalletKitty::fund_loan(RuntimeOrigin::signed(4), 0), Error::<Test>::LoanNotExists);
+    })
+}
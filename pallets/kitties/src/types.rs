@@ -3,43 +3,326 @@
 use core::fmt::Debug;
 
 use codec::*;
-use frame_support::{RuntimeDebug};
+use frame_support::{traits::Get, BoundedVec, RuntimeDebug};
+use frame_support::sp_runtime::{traits::{AtLeast32BitUnsigned, Saturating}, Perbill};
 use scale_info::TypeInfo;
+use sp_std::vec::Vec;
 
 use crate::Config;
 use crate::AccountOf;
 use crate::BalanceOf;
 use crate::TimeOf;
 
+pub type KittyIndex = u32;
+
+pub type SwapId = u32;
+
+pub type BundleId = u32;
+
+pub type LoanId = u32;
 
 #[derive(Clone, Encode, Decode, TypeInfo, MaxEncodedLen)]
 #[scale_info(skip_type_params(T))]
 pub struct Kitty<T: Config> {
+    index: KittyIndex,
     dna: [u8; 16],
     owner: AccountOf<T>,
-    price: Option<BalanceOf<T>>,
+    price: Option<(PriceCurrency<T::AssetId>, BalanceOf<T>)>,
     gender: Gender,
     created_date: TimeOf<T>,
+    parents: Option<(KittyIndex, KittyIndex)>,
+    creator: AccountOf<T>,
+    name: BoundedVec<u8, T::MaxNameLength>,
+    generation: u16,
 }
 
-#[derive(Clone, Encode, Decode, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
 pub enum Gender {
     MALE,
     FEMALE
 }
 
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum LifeStage {
+    Kitten,
+    Adult,
+    Elder,
+}
+
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum FurColor {
+    Black,
+    White,
+    Ginger,
+    Grey,
+    Golden,
+}
+
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum EyeColor {
+    Green,
+    Yellow,
+    Blue,
+    Copper,
+    Odd,
+}
+
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum Pattern {
+    Solid,
+    Tabby,
+    Spotted,
+    Tortoiseshell,
+    Colorpoint,
+}
+
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+/// visual traits of a kitty, decoded from its dna
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct KittyTraits {
+    pub fur_color: FurColor,
+    pub eye_color: EyeColor,
+    pub pattern: Pattern,
+    pub rarity: Rarity,
+    pub rarity_score: u32,
+}
+
+/// the 5 variants of a trait get rarer with the gene value, the last one is found in 1 of 64 kitties
+fn gene_tier(gene: u8) -> u8 {
+    match gene {
+        0..=127 => 0,
+        128..=191 => 1,
+        192..=231 => 2,
+        232..=251 => 3,
+        _ => 4,
+    }
+}
+
+impl KittyTraits {
+
+    /// byte 0 is the gender, bytes 1 to 3 pick the fur, eyes and pattern, byte 4 is a mutation
+    pub fn from_dna(dna: &[u8; 16]) -> Self {
+        let fur_tier = gene_tier(dna[1]);
+        let eye_tier = gene_tier(dna[2]);
+        let pattern_tier = gene_tier(dna[3]);
+
+        let fur_color = match fur_tier {
+            0 => FurColor::Black,
+            1 => FurColor::White,
+            2 => FurColor::Ginger,
+            3 => FurColor::Grey,
+            _ => FurColor::Golden,
+        };
+
+        let eye_color = match eye_tier {
+            0 => EyeColor::Green,
+            1 => EyeColor::Yellow,
+            2 => EyeColor::Blue,
+            3 => EyeColor::Copper,
+            _ => EyeColor::Odd,
+        };
+
+        let pattern = match pattern_tier {
+            0 => Pattern::Solid,
+            1 => Pattern::Tabby,
+            2 => Pattern::Spotted,
+            3 => Pattern::Tortoiseshell,
+            _ => Pattern::Colorpoint,
+        };
+
+        // each tier doubles the score of a trait, a mutation doubles the whole score
+        let mut rarity_score: u32 = [fur_tier, eye_tier, pattern_tier].iter().map(|tier| 1u32 << tier).sum();
+        if dna[4] == u8::MAX {
+            rarity_score *= 2;
+        }
+
+        let rarity = match rarity_score {
+            0..=4 => Rarity::Common,
+            5..=8 => Rarity::Uncommon,
+            9..=16 => Rarity::Rare,
+            17..=32 => Rarity::Epic,
+            _ => Rarity::Legendary,
+        };
+
+        KittyTraits { fur_color, eye_color, pattern, rarity, rarity_score }
+    }
+}
+
+/// the currency a kitty is priced in
+#[derive(Clone, Copy, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum PriceCurrency<AssetId> {
+    Native,
+    Asset(AssetId),
+}
+
+/// where the price of a sale is taken from
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum Payment<AssetId> {
+    Currency,
+    // funds reserved by an offer or a bid
+    Reserved,
+    Asset(AssetId),
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Auction<AccountId, Balance, BlockNumber> {
+    pub seller: AccountId,
+    pub reserve: Balance,
+    pub end: BlockNumber,
+    pub highest_bid: Option<(AccountId, Balance)>,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct DutchListing<Balance, BlockNumber> {
+    pub start_price: Balance,
+    pub floor_price: Balance,
+    pub start: BlockNumber,
+    pub duration: BlockNumber,
+}
+
+impl <Balance, BlockNumber> DutchListing<Balance, BlockNumber>
+where
+    Balance: AtLeast32BitUnsigned + Copy,
+    BlockNumber: AtLeast32BitUnsigned + Copy,
+{
+    /// price decays linearly from `start_price` to `floor_price` over `duration` blocks
+    pub fn price_at(&self, now: BlockNumber) -> Balance {
+        let elapsed = now.saturating_sub(self.start);
+
+        if elapsed >= self.duration {
+            return self.floor_price;
+        }
+
+        let decay = Perbill::from_rational(elapsed, self.duration);
+
+        self.start_price.saturating_sub(decay * self.start_price.saturating_sub(self.floor_price))
+    }
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Offer<Balance, BlockNumber> {
+    pub amount: Balance,
+    pub expiry: BlockNumber,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct LeaseOffer<AccountId, Balance, BlockNumber> {
+    pub to: AccountId,
+    pub fee: Balance,
+    pub duration: BlockNumber,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Lease<AccountId, BlockNumber> {
+    pub user: AccountId,
+    pub expiry: BlockNumber,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Swap<AccountId, Balance, BlockNumber> {
+    pub proposer: AccountId,
+    pub offered: KittyIndex,
+    pub wanted: KittyIndex,
+    // reserved from the proposer and paid on top of the offered kitty
+    pub top_up: Option<Balance>,
+    pub expiry: BlockNumber,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Bundle<AccountId, Balance, KittyIds> {
+    pub seller: AccountId,
+    pub kitties: KittyIds,
+    pub price: Balance,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Vault<Balance> {
+    pub kitty_id: KittyIndex,
+    // shares not yet redeemed or claimed
+    pub shares: Balance,
+    pub buyout_price: Balance,
+    pub bought_out: bool,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Stake<AccountId> {
+    pub staker: AccountId,
+    // rarity score of the kitty
+    pub weight: u32,
+    // rewards per weight already accounted for, scaled by `REWARD_PRECISION`
+    pub reward_debt: u128,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct Loan<AccountId, Balance, BlockNumber> {
+    pub borrower: AccountId,
+    // kitty locked as collateral until the loan is repaid or claimed
+    pub kitty_id: KittyIndex,
+    pub principal: Balance,
+    pub interest: Balance,
+    pub duration: BlockNumber,
+    // the lender and the deadline for repayment, once funded
+    pub funding: Option<(AccountId, BlockNumber)>,
+}
+
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct KittyMetadata<AccountId, Balance, Data> {
+    pub depositor: AccountId,
+    pub deposit: Balance,
+    pub data: Data,
+}
+
+/// a kitty as returned by the runtime API
+#[derive(Clone, Encode, Decode, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct KittyInfo<AccountId, Balance, Moment, AssetId> {
+    pub id: KittyIndex,
+    pub dna: [u8; 16],
+    pub name: Vec<u8>,
+    pub owner: AccountId,
+    pub price: Option<Balance>,
+    /// the asset the price is paid in, `None` for the native currency
+    pub price_asset: Option<AssetId>,
+    pub gender: Gender,
+    pub traits: KittyTraits,
+    pub parents: Option<(KittyIndex, KittyIndex)>,
+    pub generation: u16,
+    pub life_stage: LifeStage,
+    pub created_date: Moment,
+}
+
 impl <T: Config> Kitty<T> {
 
     pub fn new(who: AccountOf<T>, dna: [u8; 16], gender: Gender, created_date: TimeOf<T>) -> Self {
         Kitty {
+            index: 0,
             dna,
-            owner: who,
+            owner: who.clone(),
             price: None,
             gender,
             created_date,
+            parents: None,
+            creator: who,
+            name: Default::default(),
+            generation: 0,
         }
     }
 
+    pub fn index(&self) -> KittyIndex {
+        self.index
+    }
+
+    pub fn set_index(&mut self, index: KittyIndex) {
+        self.index = index;
+    }
+
     pub fn dna(&self) -> [u8; 16] {
         self.dna.clone()
     }
@@ -48,7 +331,7 @@ impl <T: Config> Kitty<T> {
         self.owner.clone()
     }
 
-    pub fn price(&self) -> Option<BalanceOf<T>> {
+    pub fn price(&self) -> Option<(PriceCurrency<T::AssetId>, BalanceOf<T>)> {
         self.price
     }
 
@@ -56,7 +339,11 @@ impl <T: Config> Kitty<T> {
         self.gender.clone()
     }
 
-    pub fn set_price(&mut self, new_price: Option<BalanceOf<T>>) {
+    pub fn traits(&self) -> KittyTraits {
+        KittyTraits::from_dna(&self.dna)
+    }
+
+    pub fn set_price(&mut self, new_price: Option<(PriceCurrency<T::AssetId>, BalanceOf<T>)>) {
         self.price = new_price;
     }
 
@@ -67,22 +354,68 @@ impl <T: Config> Kitty<T> {
     pub fn created_date(&self) -> TimeOf<T> {
         self.created_date
     }
+
+    /// stage of life at the given time, from the age since `created_date`
+    pub fn life_stage(&self, now: TimeOf<T>) -> LifeStage {
+        let age = now.saturating_sub(self.created_date);
+
+        if age < T::AdultAge::get() {
+            LifeStage::Kitten
+        } else if age < T::ElderAge::get() {
+            LifeStage::Adult
+        } else {
+            LifeStage::Elder
+        }
+    }
+
+    pub fn parents(&self) -> Option<(KittyIndex, KittyIndex)> {
+        self.parents
+    }
+
+    pub fn set_parents(&mut self, parents: (KittyIndex, KittyIndex)) {
+        self.parents = Some(parents);
+    }
+
+    pub fn generation(&self) -> u16 {
+        self.generation
+    }
+
+    pub fn set_generation(&mut self, generation: u16) {
+        self.generation = generation;
+    }
+
+    pub fn creator(&self) -> AccountOf<T> {
+        self.creator.clone()
+    }
+
+    pub fn name(&self) -> Vec<u8> {
+        self.name.to_vec()
+    }
+
+    pub fn set_name(&mut self, name: BoundedVec<u8, T::MaxNameLength>) {
+        self.name = name;
+    }
 }
 
 impl <T> sp_std::fmt::Display for Kitty<T> where T: Config {
     fn fmt(&self, f: &mut sp_std::fmt::Formatter<'_>) -> sp_std::fmt::Result {
-        write!(f, "(dna: {:?}, price: {:?}, gender: {:?}, owner: {:?}, created_date: {:?}", self.dna, self.price, self.gender, self.owner, self.created_date)
+        write!(f, "(index: {}, name: {}, dna: {:?}, price: {:?}, gender: {:?}, owner: {:?}, created_date: {:?}, parents: {:?}, generation: {}, creator: {:?}", self.index, sp_std::str::from_utf8(&self.name).unwrap_or_default(), self.dna, self.price, self.gender, self.owner, self.created_date, self.parents, self.generation, self.creator)
     }
 }
 
 impl <T> Debug for Kitty<T> where T: Config {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Kitty")
+        .field("index", &self.index)
         .field("dna", &self.dna)
         .field("owner", &self.owner)
         .field("price", &self.price)
         .field("gender", &self.gender)
         .field("create_date", &self.created_date)
+        .field("parents", &self.parents)
+        .field("generation", &self.generation)
+        .field("creator", &self.creator)
+        .field("name", &self.name)
         .finish()
     }
 }
\ No newline at end of file
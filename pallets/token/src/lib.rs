@@ -2,17 +2,38 @@
 
 pub use pallet::*;
 
+use frame_support::dispatch::{DispatchError, DispatchResult};
+
+/// Lets other pallets create and manage assets through `pallet_token`.
+pub trait AssetFactory<AccountId> {
+	type AssetId;
+
+	type Balance;
+
+	/// create a new asset administered by `owner` and return its id
+	fn create_asset(owner: &AccountId, min_balance: Self::Balance) -> Result<Self::AssetId, DispatchError>;
+
+	fn mint(asset_id: Self::AssetId, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+
+	fn burn(asset_id: Self::AssetId, who: &AccountId, amount: Self::Balance) -> DispatchResult;
+
+	fn balance(asset_id: Self::AssetId, who: &AccountId) -> Self::Balance;
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use frame_support::{pallet_prelude::{
 		*, ValueQuery, DispatchResult}, 
-		traits::{Randomness, Currency, Time, fungibles::{Create, Destroy, Transfer, metadata::Mutate as MetadataMute}, tokens::AssetId}, 
+		traits::{Randomness, Currency, Time, fungibles::{Create, Destroy, Transfer, Inspect, Mutate, metadata::Mutate as MetadataMute}, tokens::AssetId}, 
 		ensure, transactional, BoundedVec};
 	use frame_system::{pallet_prelude::OriginFor, ensure_signed};
 	use sp_runtime::traits::CheckedAdd;
 
 	use frame_support::inherent::Vec;
 
+	/// ids taken in the asset handler directly that are skipped before creating an asset fails
+	pub const MAX_ID_PROBES: u32 = 16;
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(_);
@@ -33,10 +54,13 @@ pub mod pallet {
 		type AssetHandler: Create<Self::AccountId, AssetId = Self::AssetId>
 			+ Destroy<Self::AccountId>
 			+ Transfer<Self::AccountId>
+			+ Mutate<Self::AccountId>
 			+ MetadataMute<Self::AccountId>;
 
 	}
 
+	pub(crate) type BalanceOf<T> = <<T as Config>::AssetHandler as Inspect<<T as frame_system::Config>::AccountId>>::Balance;
+
 	#[pallet::storage]
 	#[pallet::getter(fn asset_count)]
 	pub type AssetCount<T> = StorageValue<_, u32, ValueQuery, >;
@@ -49,6 +73,7 @@ pub mod pallet {
 	#[pallet::error]
 	pub enum Error<T> {
 		AssetLimited,
+		NoFreeAssetId,
 	}
 
 	#[pallet::call]
@@ -57,15 +82,8 @@ pub mod pallet {
 		pub fn create_token(origin: OriginFor<T>, asset_name: Vec<u8>, asset_symbol: Vec<u8>) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			
-			let asset_id: T::AssetId = Self::asset_count().into();
-
 			// create new token
-			T::AssetHandler::create(
-				asset_id,
-				who.clone(),
-				true,
-				1_u32.into()
-			)?;
+			let asset_id = Self::do_create(&who, 1_u32.into())?;
 
 			// set metadata for token
 			T::AssetHandler::set(
@@ -76,12 +94,62 @@ pub mod pallet {
 				10
 			)?;
 
-			let next_asset_id = Self::asset_count().checked_add(1_u32).ok_or(<Error<T>>::AssetLimited)?;
+			Ok(())
+		}
+
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn do_create(owner: &T::AccountId, min_balance: BalanceOf<T>) -> Result<T::AssetId, DispatchError> {
+			let mut asset_count = Self::asset_count();
+			let mut probes: u32 = 0;
+
+			// ids can also be taken in the asset handler directly, skip a bounded number of them
+			while T::AssetHandler::asset_exists(asset_count.into()) {
+				probes += 1;
+				ensure!(probes < MAX_ID_PROBES, <Error<T>>::NoFreeAssetId);
+
+				asset_count = asset_count.checked_add(1_u32).ok_or(<Error<T>>::AssetLimited)?;
+			}
+
+			let asset_id: T::AssetId = asset_count.into();
+
+			T::AssetHandler::create(
+				asset_id,
+				owner.clone(),
+				true,
+				min_balance
+			)?;
+
+			let next_asset_id = asset_count.checked_add(1_u32).ok_or(<Error<T>>::AssetLimited)?;
 			
 			<AssetCount<T>>::put(next_asset_id);
 
+			Ok(asset_id)
+		}
+	}
+
+	impl<T: Config> crate::AssetFactory<T::AccountId> for Pallet<T> {
+		type AssetId = T::AssetId;
+
+		type Balance = BalanceOf<T>;
+
+		fn create_asset(owner: &T::AccountId, min_balance: BalanceOf<T>) -> Result<T::AssetId, DispatchError> {
+			Self::do_create(owner, min_balance)
+		}
+
+		fn mint(asset_id: T::AssetId, who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			T::AssetHandler::mint_into(asset_id, who, amount)
+		}
+
+		fn burn(asset_id: T::AssetId, who: &T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
+			T::AssetHandler::burn_from(asset_id, who, amount)?;
+
 			Ok(())
 		}
 
+		fn balance(asset_id: T::AssetId, who: &T::AccountId) -> BalanceOf<T> {
+			T::AssetHandler::balance(asset_id, who)
+		}
 	}
 }
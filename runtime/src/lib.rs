@@ -10,7 +10,7 @@ pub mod constants;
 
 pub use constants::currency::*;
 
-use frame_support::traits::EnsureOrigin;
+use frame_support::{traits::EnsureOrigin, PalletId};
 use frame_system::{EnsureSigned, EnsureRoot};
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
@@ -58,6 +58,8 @@ pub use pallet_token;
 
 pub use pallet_assets;
 
+pub use pallet_treasury;
+
 /// An index to a block.
 pub type BlockNumber = u32;
 
@@ -79,6 +81,9 @@ pub type Hash = sp_core::H256;
 
 pub type AssetId = u32;
 
+/// A timestamp: milliseconds since the unix epoch.
+pub type Moment = u64;
+
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
 /// of data like extrinsics, allowing for them to continue syncing the network through upgrades
@@ -115,7 +120,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
 	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
 	//   the compatible custom types.
-	spec_version: 100,
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -244,7 +249,7 @@ impl pallet_grandpa::Config for Runtime {
 
 impl pallet_timestamp::Config for Runtime {
 	/// A timestamp: milliseconds since the unix epoch.
-	type Moment = u64;
+	type Moment = Moment;
 	type OnTimestampSet = Aura;
 	type MinimumPeriod = ConstU64<{ SLOT_DURATION / 2 }>;
 	type WeightInfo = ();
@@ -285,12 +290,49 @@ impl pallet_sudo::Config for Runtime {
 	type RuntimeCall = RuntimeCall;
 }
 
+parameter_types! {
+	pub const CreatorRoyalty: Perbill = Perbill::from_percent(5);
+	pub const MarketplaceFee: Perbill = Perbill::from_percent(2);
+	pub TreasuryAccount: AccountId = Treasury::account_id();
+	pub const KittyDepositPerByte: Balance = 1 * CENTS;
+	pub const KittyAdultAge: Moment = 24 * 60 * 60 * 1000;
+	pub const KittyElderAge: Moment = 365 * 24 * 60 * 60 * 1000;
+	pub const KittyActionCooldown: Moment = 60 * 60 * 1000;
+	pub const KittiesPalletId: PalletId = PalletId(*b"py/kitty");
+}
+
 impl pallet_kitties::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type KittyRandomness = RandomnessCollectiveFlip;
 	type Currency = Balances;
 	type MaxOwnerKitty = ConstU32<3>;
+	type MaxAuctionDuration = ConstU32<{ 7 * DAYS }>;
+	type MaxDutchDuration = ConstU32<{ 7 * DAYS }>;
+	type MaxAuctionsPerBlock = ConstU32<20>;
+	type MaxOffers = ConstU32<10>;
+	type MaxOffersPerBlock = ConstU32<50>;
+	type CreatorRoyalty = CreatorRoyalty;
+	type MarketplaceFee = MarketplaceFee;
+	type FeeDestination = TreasuryAccount;
 	type KittyTime = Timestamp;
+	type MaxNameLength = ConstU32<32>;
+	type MaxMetadataLength = ConstU32<128>;
+	type MetadataDepositPerByte = KittyDepositPerByte;
+	type MaxChildren = ConstU32<32>;
+	type AdultAge = KittyAdultAge;
+	type ElderAge = KittyElderAge;
+	type ActionCooldown = KittyActionCooldown;
+	type MaxLeaseDuration = ConstU32<{ 30 * DAYS }>;
+	type MaxLeasesPerBlock = ConstU32<20>;
+	type MaxSwaps = ConstU32<10>;
+	type MaxSwapsPerBlock = ConstU32<50>;
+	type MaxBundleSize = ConstU32<3>;
+	type AssetId = AssetId;
+	type Assets = Assets;
+	type AssetAdminOrigin = EnsureRoot<AccountId>;
+	type PalletId = KittiesPalletId;
+	type Tokens = Tokens;
+	type MaxBlocksBetweenUpdates = ConstU32<HOURS>;
 	type WeightInfo = pallet_kitties::weights::SubstrateWeight<Runtime>;
 }
 
@@ -325,6 +367,33 @@ impl pallet_assets::Config for Runtime {
 	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
 }
 
+parameter_types! {
+	pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = 1 * DOLLARS;
+	pub const SpendPeriod: BlockNumber = 1 * DAYS;
+	pub const Burn: Permill = Permill::from_percent(0);
+}
+
+impl pallet_treasury::Config for Runtime {
+	type PalletId = TreasuryPalletId;
+	type Currency = Balances;
+	type ApproveOrigin = EnsureRoot<AccountId>;
+	type RejectOrigin = EnsureRoot<AccountId>;
+	type RuntimeEvent = RuntimeEvent;
+	type OnSlash = Treasury;
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type ProposalBondMaximum = ();
+	type SpendPeriod = SpendPeriod;
+	type Burn = Burn;
+	type BurnDestination = ();
+	type SpendFunds = ();
+	type WeightInfo = pallet_treasury::weights::SubstrateWeight<Runtime>;
+	type MaxApprovals = ConstU32<100>;
+	type SpendOrigin = frame_support::traits::NeverEnsureOrigin<Balance>;
+}
+
 // Create the runtime by composing the FRAME pallets that were previously configured.
 construct_runtime!(
 	pub struct Runtime
@@ -345,6 +414,7 @@ construct_runtime!(
 		TemplateKitties: pallet_kitties,
 		Assets: pallet_assets,
 		Tokens: pallet_token,
+		Treasury: pallet_treasury,
 	}
 );
 
@@ -371,6 +441,13 @@ pub type UncheckedExtrinsic =
 	generic::UncheckedExtrinsic<Address, RuntimeCall, Signature, SignedExtra>;
 /// The payload being signed in transactions.
 pub type SignedPayload = generic::SignedPayload<RuntimeCall, SignedExtra>;
+/// Storage migrations run on the next runtime upgrade.
+pub type Migrations = (
+	pallet_kitties::migrations::v1::MigrateToV1<Runtime>,
+	pallet_kitties::migrations::v2::MigrateToV2<Runtime>,
+	pallet_kitties::migrations::v3::MigrateToV3<Runtime>,
+);
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = frame_executive::Executive<
 	Runtime,
@@ -378,6 +455,7 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]
@@ -543,6 +621,44 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_kitties_runtime_api::KittiesApi<Block, Hash, AccountId, Balance, Moment, AssetId> for Runtime {
+		fn kitty(kitty_id: pallet_kitties::KittyIndex) -> Option<pallet_kitties::KittyInfo<AccountId, Balance, Moment, AssetId>> {
+			TemplateKitties::kitty_info(&kitty_id)
+		}
+
+		fn kitty_by_hash(kitty_hash: Hash) -> Option<pallet_kitties::KittyInfo<AccountId, Balance, Moment, AssetId>> {
+			TemplateKitties::kitty_info_by_hash(&kitty_hash)
+		}
+
+		fn kitty_traits(kitty_id: pallet_kitties::KittyIndex) -> Option<pallet_kitties::KittyTraits> {
+			TemplateKitties::kitty_traits(&kitty_id)
+		}
+
+		fn ancestry(kitty_id: pallet_kitties::KittyIndex, depth: u32) -> Vec<(pallet_kitties::KittyIndex, u32)> {
+			TemplateKitties::ancestry(&kitty_id, depth)
+		}
+
+		fn children(kitty_id: pallet_kitties::KittyIndex) -> Vec<pallet_kitties::KittyIndex> {
+			TemplateKitties::children(kitty_id).into_inner()
+		}
+
+		fn kitties_of(owner: AccountId) -> Vec<pallet_kitties::KittyInfo<AccountId, Balance, Moment, AssetId>> {
+			TemplateKitties::kitties_of(&owner)
+		}
+
+		fn listed_kitties(offset: u32, limit: u32) -> Vec<pallet_kitties::KittyInfo<AccountId, Balance, Moment, AssetId>> {
+			TemplateKitties::listed_kitties(offset, limit)
+		}
+
+		fn count() -> u32 {
+			TemplateKitties::count()
+		}
+
+		fn user_of(kitty_id: pallet_kitties::KittyIndex) -> Option<AccountId> {
+			TemplateKitties::user_of(&kitty_id)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn benchmark_metadata(extra: bool) -> (